@@ -0,0 +1,206 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use git2::Oid;
+
+/// The name of the directory for `spectool` within the user's cache
+/// directory.
+const CACHE_DIR_NAME: &str = "spectool";
+
+/// The name of the directory containing the cached specifications.
+const SPECIFICATIONS_DIR_NAME: &str = "specifications";
+
+/// The character separating the remote URL from the reference within the name
+/// of an entry.
+const SEPARATOR: char = '@';
+
+/// A cache of specification repositories.
+///
+/// Each entry is a checkout of a single reference of a remote repository and
+/// is reused by every run that requests the same reference from the same
+/// remote.
+pub struct Cache {
+    /// The directory containing the entries.
+    root: PathBuf,
+}
+
+impl Cache {
+    /// Creates a cache within a directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Gets the cache within the user's cache directory.
+    ///
+    /// This is `$XDG_CACHE_HOME/spectool/specifications` (or
+    /// `~/.cache/spectool/specifications`) on Linux and the platform's
+    /// equivalent elsewhere. Returns `None` if the platform has no cache
+    /// directory.
+    pub fn user() -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self::new(dir.join(CACHE_DIR_NAME).join(SPECIFICATIONS_DIR_NAME)))
+    }
+
+    /// Gets the directory containing the entries.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Gets the directory of the entry for a reference of a remote.
+    ///
+    /// The directory does not exist until the reference is first checked out.
+    pub fn entry_dir(&self, url: &str, reference: &str) -> PathBuf {
+        self.root.join(format!("{}{SEPARATOR}{}", escape(url), escape(reference)))
+    }
+
+    /// Gets the entries within the cache.
+    pub fn entries(&self) -> Result<Vec<Entry>> {
+        if !self.root.exists() {
+            return Ok(Default::default());
+        }
+
+        let mut entries = std::fs::read_dir(&self.root)
+            .with_context(|| format!("reading cache directory `{}`", self.root.display()))?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                let (url, reference) = name.split_once(SEPARATOR)?;
+
+                Some(Entry {
+                    url: unescape(url)?,
+                    reference: unescape(reference)?,
+                    dir: entry.path(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|a, b| (&a.url, &a.reference).cmp(&(&b.url, &b.reference)));
+        Ok(entries)
+    }
+
+    /// Removes every entry within the cache.
+    ///
+    /// Returns the number of entries that were removed.
+    pub fn clean(&self) -> Result<usize> {
+        let entries = self.entries()?;
+
+        for entry in &entries {
+            std::fs::remove_dir_all(entry.dir())
+                .with_context(|| format!("removing cache entry `{}`", entry.dir().display()))?;
+        }
+
+        Ok(entries.len())
+    }
+}
+
+/// An entry within the cache.
+pub struct Entry {
+    /// The URL of the remote.
+    url: String,
+
+    /// The reference that is checked out.
+    reference: String,
+
+    /// The directory containing the checkout.
+    dir: PathBuf,
+}
+
+impl Entry {
+    /// Gets the URL of the remote.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Gets the reference that is checked out.
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    /// Gets the directory containing the checkout.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Gets the commit that is checked out.
+    ///
+    /// Returns `None` if the checkout is incomplete.
+    pub fn commit(&self) -> Option<Oid> {
+        let repository = git2::Repository::open(&self.dir).ok()?;
+        let commit = repository.head().ok()?.peel_to_commit().ok()?;
+        Some(commit.id())
+    }
+}
+
+/// Escapes a string for use within the name of an entry.
+///
+/// Every character other than an ASCII alphanumeric character, `-`, or `.` is
+/// written as `_` followed by the two hexadecimal digits of each of its bytes,
+/// so the escaped string may be unescaped and never contains the separator.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'.' {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("_{byte:02X}"));
+        }
+    }
+
+    escaped
+}
+
+/// Unescapes a string escaped with [`escape`].
+fn unescape(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+
+    while let Some(byte) = iter.next() {
+        if byte == b'_' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_names() {
+        let escaped = escape("https://github.com/openwdl/wdl.git");
+        assert_eq!(escaped, "https_3A_2F_2Fgithub.com_2Fopenwdl_2Fwdl.git");
+        assert_eq!(unescape(&escaped).unwrap(), "https://github.com/openwdl/wdl.git");
+
+        assert_eq!(escape("refs/pull/7/head"), "refs_2Fpull_2F7_2Fhead");
+        assert_eq!(escape("a_b@c"), "a_5Fb_40c");
+        assert_eq!(unescape("a_5Fb_40c").unwrap(), "a_b@c");
+        assert!(unescape("a_4").is_none());
+    }
+
+    #[test]
+    fn entries() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(tempdir.path());
+        assert!(cache.entries().unwrap().is_empty());
+
+        std::fs::create_dir_all(cache.entry_dir("https://example.com/wdl.git", "wdl-1.2")).unwrap();
+        std::fs::create_dir_all(cache.entry_dir("https://example.com/wdl.git", "refs/pull/7/head")).unwrap();
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].url(), "https://example.com/wdl.git");
+        assert_eq!(entries[0].reference(), "refs/pull/7/head");
+        assert_eq!(entries[1].reference(), "wdl-1.2");
+        assert!(entries[1].commit().is_none());
+
+        assert_eq!(cache.clean().unwrap(), 2);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+}
@@ -1 +1,9 @@
+pub mod cache;
+pub mod diff_spec;
+mod engine;
+pub mod matrix;
+pub mod probe;
+mod report;
+mod selection;
+mod specification;
 pub mod test;
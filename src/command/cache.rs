@@ -0,0 +1,56 @@
+use anyhow::Result;
+use clap::Parser;
+use clap::Subcommand;
+
+use crate::cache::Cache;
+
+/// A subcommand for managing the cache.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Lists the cached specifications.
+    List,
+
+    /// Removes every cached specification.
+    Clean,
+}
+
+/// Manages the specifications cached by `spectool test`.
+#[derive(Parser, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+pub fn main(args: Args) -> Result<()> {
+    let Some(cache) = Cache::user() else {
+        println!("There is no cache directory on this platform");
+        return Ok(());
+    };
+
+    match args.command {
+        Command::List => {
+            let entries = cache.entries()?;
+
+            if entries.is_empty() {
+                println!("No specifications are cached in `{}`", cache.root().display());
+                return Ok(());
+            }
+
+            for entry in entries {
+                let commit = entry
+                    .commit()
+                    .map(|commit| commit.to_string())
+                    .unwrap_or_else(|| String::from("incomplete"));
+
+                println!("{} ({}) at {}", entry.reference(), entry.url(), commit);
+                println!("  {}", entry.dir().display());
+            }
+        }
+        Command::Clean => {
+            let removed = cache.clean()?;
+            println!("Removed {} cached specification(s) from `{}`", removed, cache.root().display());
+        }
+    }
+
+    Ok(())
+}
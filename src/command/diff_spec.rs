@@ -0,0 +1,126 @@
+use anyhow::Result;
+use clap::Parser;
+use git2::Oid;
+
+use crate::command::specification;
+use crate::conformance::diff::Diff;
+use crate::conformance::Tests;
+use crate::repository::REPOSITORY_URL;
+
+/// The format of the differences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// A human-readable list of changes.
+    Text,
+    /// A JSON object (e.g., for generating release notes).
+    Json,
+}
+
+/// Compares the conformance tests of two versions of the specification.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The reference to compare from (e.g., `wdl-1.1`).
+    ///
+    /// This may be a branch, a tag, a full or abbreviated commit SHA, or a
+    /// fully qualified reference such as `refs/pull/N/head`.
+    old: String,
+
+    /// The reference to compare to (e.g., `wdl-1.2`).
+    new: String,
+
+    /// The URL of the specification repository.
+    #[arg(long, default_value = REPOSITORY_URL)]
+    url: String,
+
+    /// Whether to fast-forward the cached specifications before comparing.
+    #[arg(long, default_value_t = false)]
+    update: bool,
+
+    /// Whether to use the cached specifications without accessing the
+    /// network.
+    #[arg(long, default_value_t = false, conflicts_with = "update")]
+    offline: bool,
+
+    /// The format of the differences.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+pub fn main(args: Args) -> Result<()> {
+    let (old, old_commit) = compile(&args, &args.old)?;
+    let (new, new_commit) = compile(&args, &args.new)?;
+    let diff = Diff::new(&old, &new);
+
+    match args.format {
+        Format::Json => {
+            let output = serde_json::json!({
+                "old": { "reference": args.old, "commit": old_commit.to_string() },
+                "new": { "reference": args.new, "commit": new_commit.to_string() },
+                "diff": diff,
+            });
+
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        Format::Text => print_diff(&args, &diff, old_commit, new_commit),
+    }
+
+    Ok(())
+}
+
+/// Checks out a reference of the specification and compiles its tests.
+fn compile(args: &Args, reference: &str) -> Result<(Tests, Oid)> {
+    let (contents, commit) = specification::read()
+        .url(&args.url)
+        .reference(reference)
+        .update(args.update)
+        .offline(args.offline)
+        .call()?;
+
+    Ok((Tests::compile(contents)?, commit))
+}
+
+/// Prints the differences as a human-readable list.
+fn print_diff(args: &Args, diff: &Diff, old_commit: Oid, new_commit: Oid) {
+    println!(
+        "Comparing `{}` at commit {} with `{}` at commit {}\n",
+        args.old, old_commit, args.new, new_commit
+    );
+
+    if diff.is_empty() {
+        println!("The conformance tests are the same");
+        return;
+    }
+
+    if !diff.added().is_empty() {
+        println!("Added ({}):", diff.added().len());
+        for name in diff.added() {
+            println!("  + {}", name);
+        }
+        println!();
+    }
+
+    if !diff.removed().is_empty() {
+        println!("Removed ({}):", diff.removed().len());
+        for name in diff.removed() {
+            println!("  - {}", name);
+        }
+        println!();
+    }
+
+    if !diff.changed().is_empty() {
+        println!("Changed ({}):", diff.changed().len());
+        for change in diff.changed() {
+            let aspects = change.aspects().iter().map(ToString::to_string).collect::<Vec<_>>();
+            println!("  ~ {} ({})", change.name(), aspects.join(", "));
+        }
+        println!();
+    }
+
+    println!(
+        "{} added, {} removed, {} changed, {} unchanged",
+        diff.added().len(),
+        diff.removed().len(),
+        diff.changed().len(),
+        diff.unchanged()
+    );
+}
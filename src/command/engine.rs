@@ -0,0 +1,368 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::conformance::engine::limits::parse_memory;
+use crate::conformance::test::evaluation::evaluate;
+use crate::conformance::test::evaluation::Evaluation;
+use crate::conformance::test::selector;
+use crate::conformance::test::style;
+use crate::conformance::test::KeyStyle;
+use crate::conformance::CommandEngine;
+use crate::conformance::FailureReason;
+use crate::conformance::ResourceLimits;
+use crate::conformance::Test;
+use crate::wdl;
+
+/// The arguments for calling an engine and preparing the tests it runs.
+#[derive(clap::Args, Clone, Debug)]
+pub struct EngineArgs {
+    /// Arguments to append when running a workflow.
+    ///
+    /// Use `~{target}` for the workflow name.
+    #[arg(long, default_value = "")]
+    workflow_target_args: String,
+
+    /// Arguments to append when running a task.
+    ///
+    /// Use `~{target}` for the task name.
+    #[arg(long, default_value = "")]
+    task_target_args: String,
+
+    /// Redirect stdout to the outputs file.
+    ///
+    /// If enabled, appends `> ~{output}` at the end of the command.
+    #[arg(long, default_value_t = false)]
+    redirect_stdout: bool,
+
+    /// Execute the engine directly rather than through `bash`.
+    ///
+    /// The command template is split into arguments using shell quoting
+    /// rules before placeholders are substituted within each argument, so a
+    /// substituted value (including `~{env:NAME}`) is never quoted or split
+    /// into several arguments. The engine is then spawned directly. Shell
+    /// features such as pipes, redirections, and variable expansion are not
+    /// available in this mode.
+    #[arg(long, default_value_t = false)]
+    exec: bool,
+
+    /// Resolve relative `File` and `Directory` inputs to absolute paths.
+    ///
+    /// String inputs that name an existing resource file are rewritten to
+    /// the absolute path of that resource within the test's working
+    /// directory. Only inputs declared as `File` or `Directory` in the WDL
+    /// are rewritten, unless the input declaration cannot be found.
+    #[arg(long, default_value_t = false)]
+    resolve_input_paths: bool,
+
+    /// A `jq` selector to apply to the inputs before writing `inputs.json`.
+    ///
+    /// This allows transforming the input JSON for engines with different
+    /// input conventions. It is applied after `--input-style`.
+    ///
+    /// Uses `jq` syntax (e.g., `'{inputs: .}'`).
+    #[arg(long)]
+    input_selector: Option<String>,
+
+    /// The style of the input keys expected by the engine.
+    ///
+    /// Inputs are converted from the flattened `target.name` style used by
+    /// the specification examples into this style.
+    #[arg(long, value_name = "STYLE")]
+    input_style: Option<KeyStyle>,
+
+    /// A `jq` selector to apply to `outputs.json` before validation.
+    ///
+    /// This allows transforming the output JSON before comparing against expected output.
+    /// For example, `--output-selector '.outputs'` will extract the `outputs` field from the output.
+    ///
+    /// Uses `jq` syntax (e.g., `'.outputs'`, `'.result.data[0]'`, etc.).
+    #[arg(long)]
+    output_selector: Option<String>,
+
+    /// The style of the output keys emitted by the engine.
+    ///
+    /// Outputs are normalized from this style into the flattened
+    /// `target.name` style used by the specification examples. This is
+    /// applied after `--output-selector`.
+    #[arg(long, value_name = "STYLE")]
+    output_style: Option<KeyStyle>,
+
+    /// A command that only validates a document without executing it.
+    ///
+    /// When provided, tests marked with `fail: true` and a `fail_phase` are
+    /// first checked with this command. Tests expected to fail during `parse`
+    /// or `analysis` must be rejected by it, while tests expected to fail
+    /// during `runtime` must be accepted by it. Any rejection by the check
+    /// command satisfies both `parse` and `analysis`, as the two phases are
+    /// not distinguished.
+    ///
+    /// Supports the same substitutions as the main command.
+    #[arg(long, value_name = "COMMAND")]
+    check_command: Option<String>,
+
+    /// The maximum virtual memory of each engine process (e.g., `512M` or
+    /// `4G`).
+    ///
+    /// Tests may override this with `max_memory` in their configuration.
+    /// Resource limits are only applied on Unix platforms.
+    ///
+    /// Exceeding this limit is inferred heuristically: a failed process whose
+    /// stderr reports an allocation failure (e.g., `out of memory` or
+    /// `MemoryError`) is reported as exceeding the limit, unless the test
+    /// expects that error through `expected_stderr`.
+    #[arg(long, value_name = "SIZE", value_parser = parse_memory)]
+    max_memory: Option<u64>,
+
+    /// The maximum CPU time of each engine process in seconds.
+    ///
+    /// The limit applies to each process separately, and a failure is only
+    /// reported as exceeding it when the engine process is terminated by
+    /// `SIGXCPU` or `SIGKILL` from the limit. Tests may override this with
+    /// `max_cpu_time` in their configuration.
+    #[arg(long, value_name = "SECONDS")]
+    max_cpu_time: Option<u64>,
+
+    /// The maximum number of files each engine process may have open.
+    ///
+    /// Tests may override this with `max_open_files` in their configuration.
+    ///
+    /// Exceeding this limit is inferred heuristically: a failed process whose
+    /// stderr reports `too many open files` is reported as exceeding the
+    /// limit, unless the test expects that error through `expected_stderr`.
+    #[arg(long, value_name = "COUNT")]
+    max_open_files: Option<u64>,
+
+    /// WDL version to inject into test files.
+    ///
+    /// Replaces the `version` statement in each test file before writing to disk.
+    /// For example, `--inject-wdl-version development` will replace `version 1.2`
+    /// with `version development`.
+    ///
+    /// This is useful when testing against engines that require specific version strings.
+    #[arg(long, value_name = "VERSION")]
+    inject_wdl_version: Option<String>,
+
+    /// The command to call for each execution.
+    ///
+    #[arg(help = r#"The command to call for each execution.
+
+The following substitutions are supported:
+
+  - `~{path}` is the path to the file.
+  - `~{input}` is the path to the inputs.json file.
+  - `~{output}` is the path to the outputs.json file.
+  - `~{target}` is the name of the workflow or task.
+  - `~{workdir}` is the path to the working directory of the test.
+  - `~{data_dir}` is the path to the data directory of the test.
+  - `~{test_name}` is the name of the test.
+  - `~{target_kind}` is either `workflow` or `task`.
+  - `~{root_dir}` is the path to the conformance tests directory.
+  - `~{wdl_version}` is the version of the WDL file.
+  - `~{env:NAME}` is the value of the `NAME` environment variable.
+
+Paths are shell-quoted when necessary. Unknown placeholders are rejected."#)]
+    command: String,
+}
+
+impl EngineArgs {
+    /// Gets the WDL version to inject into test files.
+    pub(crate) fn inject_wdl_version(&self) -> Option<String> {
+        self.inject_wdl_version.clone()
+    }
+
+    /// Gets the engine options that were provided as pairs of a name and a
+    /// value (e.g., for describing the engine within a report).
+    pub(crate) fn metadata(&self) -> Vec<(&'static str, String)> {
+        let mut metadata = vec![("Command", self.command.clone())];
+
+        let mut push = |name, value: Option<String>| {
+            if let Some(value) = value {
+                metadata.push((name, value));
+            }
+        };
+
+        push("Check command", self.check_command.clone());
+        push("Workflow target args", Some(self.workflow_target_args.clone()).filter(|args| !args.is_empty()));
+        push("Task target args", Some(self.task_target_args.clone()).filter(|args| !args.is_empty()));
+        push("Redirect stdout", self.redirect_stdout.then(|| String::from("yes")));
+        push("Exec", self.exec.then(|| String::from("yes")));
+        push("Resolve input paths", self.resolve_input_paths.then(|| String::from("yes")));
+        push("Input style", self.input_style.map(|style| style.to_string()));
+        push("Input selector", self.input_selector.clone());
+        push("Output selector", self.output_selector.clone());
+        push("Output style", self.output_style.map(|style| style.to_string()));
+        push("Max memory", self.max_memory.map(|bytes| format!("{bytes} bytes")));
+        push("Max CPU time", self.max_cpu_time.map(|secs| format!("{secs}s")));
+        push("Max open files", self.max_open_files.map(|count| count.to_string()));
+        push("Injected WDL version", self.inject_wdl_version.clone());
+
+        metadata
+    }
+
+    /// Builds and validates the engine.
+    pub(crate) fn engine(&self) -> Result<CommandEngine> {
+        let engine = CommandEngine::builder()
+            .command(self.command.clone())
+            .maybe_check_command(self.check_command.clone())
+            .workflow_target_args(self.workflow_target_args.clone())
+            .task_target_args(self.task_target_args.clone())
+            .redirect_stdout(self.redirect_stdout)
+            .exec(self.exec)
+            .limits(
+                ResourceLimits::builder()
+                    .maybe_memory(self.max_memory)
+                    .maybe_cpu_time(self.max_cpu_time)
+                    .maybe_open_files(self.max_open_files)
+                    .build(),
+            )
+            .build();
+
+        engine.validate()?;
+        Ok(engine)
+    }
+
+    /// Creates an `input.json` file.
+    ///
+    /// Relative paths are resolved first, if requested. The inputs are then
+    /// converted into the requested style and passed through the input
+    /// selector, if either is provided.
+    pub(crate) fn create_input_json(&self, test: &Test, work_dir: &Path) -> Result<PathBuf, FailureReason> {
+        let input = match test.input() {
+            Some(value) => {
+                let value = if self.resolve_input_paths {
+                    resolve_input_paths(test, value, &work_dir.join("data"))
+                } else {
+                    value.clone()
+                };
+
+                let value = match self.input_style {
+                    Some(input_style) => {
+                        let target = test.target().expect("target should be inferred");
+                        style::unflatten(&value, input_style, target.name()).map_err(|e| {
+                            FailureReason::InputError(format!(
+                                "failed to convert inputs to `{}` style: {}",
+                                input_style, e
+                            ))
+                        })?
+                    }
+                    None => value,
+                };
+
+                let value = match self.input_selector.as_deref() {
+                    Some(selector) => selector::apply(selector, &value)?,
+                    None => value,
+                };
+
+                serde_json::to_string_pretty(&value)
+                    .map_err(|e| FailureReason::InputError(format!("serializing input file: {}", e)))?
+            }
+            None => Default::default(),
+        };
+
+        let input_file_path = work_dir.join("inputs.json");
+        std::fs::write(&input_file_path, input)
+            .map_err(|e| FailureReason::InputError(format!("writing `inputs.json` file: {}", e)))?;
+
+        Ok(input_file_path)
+    }
+
+    /// Executes a test with the engine and evaluates the result.
+    pub(crate) fn evaluate(&self, test: &Test, engine: &CommandEngine, input: &Path, workdir: &Path) -> Evaluation {
+        evaluate()
+            .test(test)
+            .engine(engine)
+            .input(input)
+            .workdir(workdir)
+            .maybe_output_selector(self.output_selector.as_deref())
+            .maybe_output_style(self.output_style)
+            .call()
+    }
+}
+
+/// Creates an empty working directory for a test and copies the data
+/// directory into it.
+///
+/// Any existing working directory is replaced.
+pub(crate) fn prepare_workdir(source_data_dir: &Path, workdir: &Path) -> Result<()> {
+    if workdir.exists() {
+        std::fs::remove_dir_all(workdir)
+            .with_context(|| format!("removing working directory `{}`", workdir.display()))?;
+    }
+    std::fs::create_dir_all(workdir)
+        .with_context(|| format!("creating working directory `{}`", workdir.display()))?;
+
+    if source_data_dir.exists() {
+        let mut options = fs_extra::dir::CopyOptions::new();
+        options.overwrite = true;
+        options.copy_inside = true;
+        fs_extra::dir::copy(source_data_dir, workdir, &options).with_context(|| {
+            format!(
+                "copying data directory `{}` into `{}`",
+                source_data_dir.display(),
+                workdir.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Rewrites string inputs that name existing files within the data directory
+/// to absolute paths.
+///
+/// Inputs declared in the WDL are only rewritten if their type contains a
+/// `File` or `Directory`. Inputs without a matching declaration (e.g., nested
+/// call inputs) are rewritten whenever they name an existing file.
+fn resolve_input_paths(test: &Test, input: &Value, data_dir: &Path) -> Value {
+    let Some(obj) = input.as_object() else {
+        return input.clone();
+    };
+
+    let target = test.target().expect("target should be inferred");
+    let decls = wdl::parse_input_declarations(test.src(), target);
+
+    let resolved = obj
+        .iter()
+        .map(|(key, value)| {
+            let name = key
+                .strip_prefix(target.name())
+                .and_then(|name| name.strip_prefix('.'))
+                .unwrap_or(key);
+
+            let is_path = decls
+                .iter()
+                .find(|decl| decl.name() == name)
+                .map(|decl| decl.is_path())
+                .unwrap_or(true);
+
+            if is_path {
+                (key.clone(), resolve_paths(value, data_dir))
+            } else {
+                (key.clone(), value.clone())
+            }
+        })
+        .collect();
+
+    Value::Object(resolved)
+}
+
+/// Recursively rewrites strings that name existing files within the data
+/// directory to absolute paths.
+fn resolve_paths(value: &Value, data_dir: &Path) -> Value {
+    match value {
+        Value::String(s) if !s.is_empty() && Path::new(s).is_relative() && data_dir.join(s).exists() => {
+            Value::String(data_dir.join(s).display().to_string())
+        }
+        Value::Array(values) => Value::Array(values.iter().map(|v| resolve_paths(v, data_dir)).collect()),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(key, v)| (key.clone(), resolve_paths(v, data_dir)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,517 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use clap::Parser;
+
+use crate::command::engine::prepare_workdir;
+use crate::command::engine::EngineArgs;
+use crate::command::selection::Selection;
+use crate::command::selection::Selector;
+use crate::command::specification;
+use crate::command::specification::SPEC_FILE_NAME;
+use crate::conformance::Capability;
+use crate::conformance::Document;
+use crate::conformance::TestResult;
+use crate::conformance::test::Runner;
+use crate::repository::REPOSITORY_URL;
+
+/// The format of the compatibility matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// A table for the terminal.
+    Text,
+    /// A GitHub-flavored Markdown table.
+    Markdown,
+    /// A JSON object.
+    Json,
+}
+
+/// The definition of an engine within the matrix.
+#[derive(Debug, Clone)]
+pub struct EngineDefinition {
+    /// The name of the engine.
+    name: String,
+
+    /// The arguments for the engine.
+    args: EngineArgs,
+}
+
+/// The arguments of an engine definition.
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+struct EngineDefinitionArgs {
+    /// The arguments for the engine.
+    #[command(flatten)]
+    engine: EngineArgs,
+}
+
+/// Parses an engine definition in the form `NAME=ARGS`.
+fn parse_engine_definition(s: &str) -> Result<EngineDefinition, String> {
+    let (name, args) = s
+        .split_once('=')
+        .ok_or_else(|| String::from("expected an engine definition in the form `NAME=ARGS`"))?;
+
+    // NOTE: the name is used as a directory name, so it must not be `.`, `..`,
+    // or otherwise start with anything other than an alphanumeric character.
+    let starts_alphanumeric = name.starts_with(|c: char| c.is_ascii_alphanumeric());
+    if !starts_alphanumeric || !name.chars().all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c)) {
+        return Err(format!(
+            "invalid engine name `{name}`: names must start with an alphanumeric character and may only contain \
+             alphanumeric characters, `_`, `-`, or `.`"
+        ));
+    }
+
+    let args = shlex::split(args).ok_or_else(|| format!("invalid quoting in the arguments of engine `{name}`"))?;
+    let args = EngineDefinitionArgs::try_parse_from(args)
+        .map_err(|e| {
+            // NOTE: only the first line of the error is kept, as the rest
+            // refers to the usage of the whole command.
+            let message = e.render().to_string();
+            let message = message.lines().next().unwrap_or_default().trim_start_matches("error: ").to_string();
+            format!("invalid arguments for engine `{name}`: {message}")
+        })?;
+
+    Ok(EngineDefinition {
+        name: name.to_string(),
+        args: args.engine,
+    })
+}
+
+/// Compares the conformance of several engines.
+///
+/// The suite is run once per engine, and the results are emitted as a table
+/// of tests by engines. Tests on which the engines disagree (i.e., at least
+/// one engine passed and another failed) are highlighted, as they may point to
+/// ambiguity within the specification.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// An engine to run the suite with, in the form `NAME=ARGS`.
+    ///
+    /// The arguments are those of `spectool test` for calling an engine,
+    /// including the command (e.g., `sprocket=--redirect-stdout "sprocket run
+    /// ~{path} ~{input} -e ~{target}"`). Names start with an alphanumeric
+    /// character and may also contain `_`, `-`, or `.`. This must be repeated
+    /// for each engine.
+    #[arg(
+        long = "engine",
+        value_name = "NAME=ARGS",
+        required = true,
+        value_parser = parse_engine_definition
+    )]
+    engines: Vec<EngineDefinition>,
+
+    /// The branch to check out.
+    ///
+    /// This may also be a tag, a full or abbreviated commit SHA, or a fully
+    /// qualified reference such as `refs/pull/N/head`.
+    #[arg(short, long, default_value = "wdl-1.2")]
+    branch: String,
+
+    /// The URL of the specification repository.
+    #[arg(long, default_value = REPOSITORY_URL)]
+    url: String,
+
+    /// A markdown file containing the conformance tests (`-` for stdin).
+    ///
+    /// This may be repeated or be a glob, as with `spectool test`.
+    #[arg(long = "spec-file", value_name = "PATH", conflicts_with_all = ["branch", "url", "offline"])]
+    spec_files: Vec<PathBuf>,
+
+    /// Whether to use the cached specification without accessing the
+    /// network.
+    #[arg(long, default_value_t = false)]
+    offline: bool,
+
+    /// Runtime capabilities available for tests (comma-separated).
+    ///
+    /// Tests requiring capabilities not in this list are skipped for every
+    /// engine.
+    #[arg(long, value_delimiter = ',', conflicts_with = "all_capabilities")]
+    capabilities: Vec<Capability>,
+
+    /// Enable all runtime capabilities, including custom ones.
+    #[arg(long, conflicts_with = "capabilities")]
+    all_capabilities: bool,
+
+    /// Only run tests matching these patterns (comma-separated).
+    #[arg(long, value_delimiter = ',', conflicts_with = "exclude")]
+    include: Vec<String>,
+
+    /// Skip tests matching these patterns (comma-separated).
+    #[arg(long, value_delimiter = ',', conflicts_with = "include")]
+    exclude: Vec<String>,
+
+    /// The format of the compatibility matrix.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+/// The results of the engines for a single test.
+struct Row {
+    /// The name of the test.
+    name: String,
+
+    /// The result of each engine that ran the test, keyed by the name of
+    /// the engine.
+    results: HashMap<String, TestResult>,
+}
+
+impl Row {
+    /// Gets the result of an engine, if the engine ran the test.
+    fn result(&self, engine: &str) -> Option<&TestResult> {
+        self.results.get(engine)
+    }
+
+    /// Returns whether at least one engine passed the test and another
+    /// failed it.
+    fn is_disagreement(&self) -> bool {
+        self.results.values().any(TestResult::is_passed) && self.results.values().any(TestResult::is_failed)
+    }
+}
+
+pub fn main(args: Args) -> Result<()> {
+    let names = args.engines.iter().map(|engine| engine.name.as_str()).collect::<Vec<_>>();
+    if let Some(name) = names.iter().enumerate().find_map(|(i, name)| names[..i].contains(name).then_some(name)) {
+        anyhow::bail!("engine `{name}` is defined more than once");
+    }
+
+    // NOTE: progress is written to stderr so that the matrix may be
+    // redirected from stdout.
+    let documents = if args.spec_files.is_empty() {
+        let (contents, commit) = specification::read()
+            .url(&args.url)
+            .reference(&args.branch)
+            .offline(args.offline)
+            .call()?;
+
+        eprintln!("Using specification `{}` at commit {}", args.branch, commit);
+        vec![Document::new(SPEC_FILE_NAME, contents)]
+    } else {
+        specification::read_files(&args.spec_files)?
+    };
+
+    for lint in args.capabilities.iter().filter_map(Capability::lint) {
+        eprintln!("warning: {lint}");
+    }
+
+    let tempdir = tempfile::tempdir().context("creating temporary directory for the matrix")?;
+    let mut rows: Vec<Row> = Vec::new();
+
+    for (i, definition) in args.engines.iter().enumerate() {
+        eprintln!("Running the conformance tests with `{}`", definition.name);
+
+        // NOTE: the lints of the tests are only reported for the first
+        // engine, as every engine runs the same tests.
+        let results = run_engine(&args, definition, &documents, &tempdir.path().join(&definition.name), i == 0)?;
+
+        // NOTE: results are keyed by the name of the test and the engine
+        // rather than by position, so that a result is never attributed to
+        // another test or engine when the engines do not run the same tests.
+        for (name, result) in results {
+            let row = match rows.iter().position(|row| row.name == name) {
+                Some(i) => &mut rows[i],
+                None => {
+                    rows.push(Row {
+                        name,
+                        results: HashMap::new(),
+                    });
+                    rows.last_mut().unwrap()
+                }
+            };
+
+            row.results.insert(definition.name.clone(), result);
+        }
+    }
+
+    eprintln!();
+
+    match args.format {
+        Format::Text => print_text(&names, &rows),
+        Format::Markdown => print_markdown(&names, &rows),
+        Format::Json => print_json(&names, &rows)?,
+    }
+
+    Ok(())
+}
+
+/// Compiles and runs the suite with an engine.
+///
+/// Returns the name and result of each selected test.
+fn run_engine(
+    args: &Args,
+    definition: &EngineDefinition,
+    documents: &[Document],
+    dir: &std::path::Path,
+    report_lints: bool,
+) -> Result<Vec<(String, TestResult)>> {
+    let engine = definition
+        .args
+        .engine()
+        .with_context(|| format!("building engine `{}`", definition.name))?;
+    let runner = Runner::compile_documents(
+        dir.join("tests"),
+        documents,
+        false,
+        definition.args.inject_wdl_version(),
+    )?;
+
+    if report_lints {
+        for lint in runner.lints() {
+            eprintln!("warning: {lint}");
+        }
+    }
+
+    let selector = Selector::builder()
+        .include(&args.include)
+        .exclude(&args.exclude)
+        .maybe_capabilities((!args.all_capabilities).then_some(args.capabilities.as_slice()))
+        .build();
+
+    let mut results = Vec::new();
+
+    for test in runner.tests() {
+        let test_name = test.file_name().trim_end_matches(".wdl");
+        match selector.select(test) {
+            Selection::Excluded => continue,
+            Selection::Skipped(reason) => {
+                results.push((test.file_name().to_string(), TestResult::Skipped(reason)));
+                continue;
+            }
+            Selection::Selected => {}
+        }
+
+        let workdir = dir.join("workdirs").join(test_name);
+        prepare_workdir(&runner.root_dir().join("data"), &workdir)?;
+
+        let result = match definition.args.create_input_json(test, &workdir) {
+            Ok(input_file) => definition.args.evaluate(test, &engine, &input_file, &workdir).into_result(),
+            Err(reason) => TestResult::Failed(reason),
+        };
+
+        tracing::info!("test `{}` with engine `{}`: {:?}", test.file_name(), definition.name, result);
+
+        std::fs::remove_dir_all(&workdir)
+            .with_context(|| format!("removing working directory `{}`", workdir.display()))?;
+        results.push((test.file_name().to_string(), result));
+    }
+
+    Ok(results)
+}
+
+/// Gets the status of a result (e.g., `PASS`).
+fn status(result: &TestResult) -> &'static str {
+    match result {
+        TestResult::Passed => "PASS",
+        TestResult::Failed(_) => "FAIL",
+        TestResult::Skipped(_) => "SKIP",
+    }
+}
+
+/// Gets the reason for a result, if it did not pass.
+fn reason(result: &TestResult) -> Option<String> {
+    match result {
+        TestResult::Passed => None,
+        TestResult::Failed(reason) => Some(reason.to_string()),
+        TestResult::Skipped(reason) => Some(reason.to_string()),
+    }
+}
+
+/// Counts the tests passed, failed, and skipped by each engine.
+fn totals(names: &[&str], rows: &[Row]) -> Vec<(usize, usize, usize)> {
+    names
+        .iter()
+        .map(|name| {
+            let results = rows.iter().filter_map(|row| row.result(name));
+            results.fold((0, 0, 0), |(passed, failed, skipped), result| match result {
+                TestResult::Passed => (passed + 1, failed, skipped),
+                TestResult::Failed(_) => (passed, failed + 1, skipped),
+                TestResult::Skipped(_) => (passed, failed, skipped + 1),
+            })
+        })
+        .collect()
+}
+
+/// Prints the matrix as a table for the terminal.
+///
+/// Disagreements are marked with `!` and highlighted.
+fn print_text(names: &[&str], rows: &[Row]) {
+    /// The width of each engine column.
+    const STATUS_WIDTH: usize = 4;
+
+    let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or_default().max(4);
+    let widths = names.iter().map(|name| name.len().max(STATUS_WIDTH)).collect::<Vec<_>>();
+
+    print!("  {:name_width$}", "Test");
+    for (name, width) in names.iter().zip(&widths) {
+        print!("  {:width$}", name);
+    }
+    println!();
+
+    for row in rows {
+        let disagreement = row.is_disagreement();
+        let (marker, bold, reset) = if disagreement { ("!", "\x1b[1m", "\x1b[0m") } else { (" ", "", "") };
+
+        print!("{marker} {bold}{:name_width$}{reset}", row.name);
+        for (name, width) in names.iter().zip(&widths) {
+            let Some(result) = row.result(name) else {
+                print!("  {:width$}", "-");
+                continue;
+            };
+
+            let color = match result {
+                TestResult::Passed => "\x1b[32m",
+                TestResult::Failed(_) => "\x1b[31m",
+                TestResult::Skipped(_) => "\x1b[33m",
+            };
+
+            print!("  {color}{:width$}\x1b[0m", status(result));
+        }
+        println!();
+    }
+
+    println!();
+    for (name, (passed, failed, skipped)) in names.iter().zip(totals(names, rows)) {
+        println!("{name}: {passed} passed, {failed} failed, {skipped} skipped");
+    }
+
+    let disagreements = rows.iter().filter(|row| row.is_disagreement()).count();
+    println!("\n{disagreements} test(s) with disagreements (marked with `!`)");
+}
+
+/// Prints the matrix as a GitHub-flavored Markdown table.
+///
+/// Disagreements are marked with a warning sign.
+fn print_markdown(names: &[&str], rows: &[Row]) {
+    println!("| Test | {} |", names.join(" | "));
+    println!("|---|{}", "---|".repeat(names.len()));
+
+    for row in rows {
+        let name = if row.is_disagreement() {
+            format!("⚠️ **`{}`**", row.name)
+        } else {
+            format!("`{}`", row.name)
+        };
+
+        let cells = names
+            .iter()
+            .map(|name| match row.result(name) {
+                Some(TestResult::Passed) => "✅",
+                Some(TestResult::Failed(_)) => "❌",
+                Some(TestResult::Skipped(_)) => "⏭️",
+                None => "➖",
+            })
+            .collect::<Vec<_>>();
+
+        println!("| {} | {} |", name, cells.join(" | "));
+    }
+
+    let totals = totals(names, rows);
+    let cells = totals
+        .iter()
+        .map(|(passed, failed, skipped)| format!("{passed} ✅ {failed} ❌ {skipped} ⏭️"))
+        .collect::<Vec<_>>();
+    println!("| **Total** | {} |", cells.join(" | "));
+
+    let disagreements = rows.iter().filter(|row| row.is_disagreement()).count();
+    println!("\n{disagreements} test(s) with disagreements (marked with ⚠️)");
+}
+
+/// Prints the matrix as a JSON object.
+fn print_json(names: &[&str], rows: &[Row]) -> Result<()> {
+    let tests = rows
+        .iter()
+        .map(|row| {
+            let results = names
+                .iter()
+                .filter_map(|name| row.result(name).map(|result| (name, result)))
+                .map(|(name, result)| {
+                    let value = serde_json::json!({
+                        "status": status(result).to_lowercase(),
+                        "reason": reason(result),
+                    });
+
+                    (name.to_string(), value)
+                })
+                .collect::<serde_json::Map<_, _>>();
+
+            serde_json::json!({
+                "name": row.name,
+                "results": results,
+                "disagreement": row.is_disagreement(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let output = serde_json::json!({
+        "engines": names,
+        "tests": tests,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::FailureReason;
+    use crate::conformance::SkipReason;
+
+    #[test]
+    fn parses_engine_definitions() {
+        let definition = parse_engine_definition(r#"sprocket=--redirect-stdout "sprocket run ~{path}""#).unwrap();
+        assert_eq!(definition.name, "sprocket");
+
+        assert!(parse_engine_definition("sprocket").is_err());
+        assert!(parse_engine_definition(r#"=--redirect-stdout "sprocket run ~{path}""#).is_err());
+        assert!(parse_engine_definition(r#"a/b="sprocket run ~{path}""#).is_err());
+        assert!(parse_engine_definition(r#"..="sprocket run ~{path}""#).is_err());
+        assert!(parse_engine_definition(r#".="sprocket run ~{path}""#).is_err());
+        assert!(parse_engine_definition(r#"-x="sprocket run ~{path}""#).is_err());
+        assert!(parse_engine_definition(r#"v1.2="sprocket run ~{path}""#).is_ok());
+        assert!(parse_engine_definition(r#"sprocket=--bogus "sprocket run ~{path}""#).is_err());
+    }
+
+    #[test]
+    fn disagreements() {
+        let row = |results: Vec<TestResult>| Row {
+            name: String::from("test.wdl"),
+            results: results
+                .into_iter()
+                .enumerate()
+                .map(|(i, result)| (format!("engine{i}"), result))
+                .collect(),
+        };
+
+        let failed = || TestResult::Failed(FailureReason::UnexpectedSuccess);
+        let skipped = || TestResult::Skipped(SkipReason::Ignored);
+
+        assert!(row(vec![TestResult::Passed, failed()]).is_disagreement());
+        assert!(row(vec![failed(), skipped(), TestResult::Passed]).is_disagreement());
+        assert!(!row(vec![TestResult::Passed, skipped()]).is_disagreement());
+        assert!(!row(vec![failed(), failed()]).is_disagreement());
+    }
+
+    #[test]
+    fn totals_by_engine() {
+        let rows = [
+            Row {
+                name: String::from("a.wdl"),
+                results: HashMap::from([
+                    (String::from("x"), TestResult::Passed),
+                    (String::from("y"), TestResult::Skipped(SkipReason::Ignored)),
+                ]),
+            },
+            Row {
+                name: String::from("b.wdl"),
+                results: HashMap::from([(String::from("y"), TestResult::Failed(FailureReason::UnexpectedSuccess))]),
+            },
+        ];
+
+        assert!(rows[0].result("x").is_some_and(TestResult::is_passed));
+        assert!(rows[1].result("x").is_none());
+        assert_eq!(totals(&["x", "y"], &rows), vec![(1, 0, 0), (0, 1, 1)]);
+    }
+}
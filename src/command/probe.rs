@@ -0,0 +1,155 @@
+use anyhow::Context as _;
+use anyhow::Result;
+use clap::Parser;
+
+use crate::command::engine::prepare_workdir;
+use crate::command::engine::EngineArgs;
+use crate::command::test::print_result;
+use crate::conformance::probe;
+use crate::conformance::test::Runner;
+use crate::conformance::Capability;
+use crate::conformance::CommandEngine;
+use crate::conformance::TestResult;
+
+/// Detects the capabilities of an engine.
+///
+/// A small built-in WDL probe is run through the engine for each capability,
+/// and the capability is reported as supported if the engine honors it.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The arguments for the engine.
+    #[command(flatten)]
+    engine: EngineArgs,
+}
+
+/// The results of probing an engine.
+pub(crate) struct Probes {
+    /// The WDL version the probes were run with.
+    version: String,
+
+    /// The result of the probe for the WDL version.
+    version_result: TestResult,
+
+    /// The result of the probe for each capability.
+    ///
+    /// The capabilities are only probed if the engine passed the probe for
+    /// the WDL version.
+    capabilities: Vec<(Capability, TestResult)>,
+}
+
+impl Probes {
+    /// Gets the reason the engine failed the probe for the WDL version, if
+    /// it did.
+    ///
+    /// In that case, the capabilities of the engine are unknown rather than
+    /// unsupported.
+    pub(crate) fn version_mismatch(&self) -> Option<String> {
+        let reason = match &self.version_result {
+            TestResult::Passed => return None,
+            TestResult::Failed(reason) => reason.to_string(),
+            TestResult::Skipped(reason) => reason.to_string(),
+        };
+
+        Some(format!("the engine does not run the WDL {} probes ({reason})", self.version))
+    }
+
+    /// Gets the capabilities whose probes passed.
+    pub(crate) fn supported(&self) -> Vec<Capability> {
+        self.capabilities
+            .iter()
+            .filter(|(_, result)| result.is_passed())
+            .map(|(capability, _)| capability.clone())
+            .collect()
+    }
+}
+
+pub fn main(args: Args) -> Result<()> {
+    let engine = args.engine.engine()?;
+    let probes = run_probes(&args.engine, &engine)?;
+
+    print_probe(&format!("WDL {}", probes.version), &probes.version_result);
+    for (capability, result) in &probes.capabilities {
+        print_probe(&capability.to_string(), result);
+    }
+
+    println!();
+    if let Some(mismatch) = probes.version_mismatch() {
+        println!("The capabilities of the engine are unknown: {mismatch}");
+        return Ok(());
+    }
+
+    let supported = probes.supported();
+    if supported.is_empty() {
+        println!("The engine supports none of the probed capabilities");
+    } else {
+        println!(
+            "Supported capabilities: --capabilities {}",
+            supported.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints the result of a probe.
+fn print_probe(name: &str, result: &TestResult) {
+    match result {
+        TestResult::Passed => print_result(name, "PASS", None, None, None),
+        TestResult::Failed(reason) => print_result(name, "FAIL", Some(&reason.to_string()), None, None),
+        TestResult::Skipped(reason) => print_result(name, "SKIP", Some(&reason.to_string()), None, None),
+    }
+}
+
+/// Runs the probe for the WDL version and then the probe for each capability
+/// through an engine.
+///
+/// The capabilities are not probed if the engine fails the probe for the WDL
+/// version, as every probe would fail for the same reason. The probes are
+/// written to and executed within a temporary directory that is removed
+/// afterwards.
+pub(crate) fn run_probes(args: &EngineArgs, engine: &CommandEngine) -> Result<Probes> {
+    let tempdir = tempfile::tempdir().context("creating temporary directory for the probes")?;
+    let runner = Runner::compile(
+        tempdir.path().join("probes"),
+        probe::PROBES,
+        false,
+        args.inject_wdl_version(),
+    )?;
+
+    let mut version_result = None;
+    let mut capabilities = Vec::new();
+
+    for test in runner.tests() {
+        // NOTE: the probe for the WDL version comes first.
+        if version_result.as_ref().is_some_and(|result: &TestResult| !result.is_passed()) {
+            break;
+        }
+
+        let capability = probe::capability(test)?.cloned();
+
+        let workdir = tempdir.path().join("workdirs").join(test.file_name().trim_end_matches(".wdl"));
+        prepare_workdir(&runner.root_dir().join("data"), &workdir)?;
+
+        let result = match args.create_input_json(test, &workdir) {
+            Ok(input_file) => args.evaluate(test, engine, &input_file, &workdir).into_result(),
+            Err(reason) => TestResult::Failed(reason),
+        };
+
+        match capability {
+            Some(capability) => {
+                tracing::info!("probe for capability `{}`: {:?}", capability, result);
+                capabilities.push((capability, result));
+            }
+            None => {
+                tracing::info!("probe for the WDL version: {:?}", result);
+                version_result = Some(result);
+            }
+        }
+    }
+
+    Ok(Probes {
+        version: args.inject_wdl_version().unwrap_or_else(|| probe::VERSION.to_string()),
+        version_result: version_result.context("the probes must include a probe for the WDL version")?,
+        capabilities,
+    })
+}
@@ -0,0 +1,550 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use git2::Oid;
+use serde_json::Value;
+
+use crate::conformance::Execution;
+use crate::conformance::ResourceUsage;
+use crate::conformance::SkipReason;
+use crate::conformance::Test;
+use crate::conformance::TestResult;
+
+/// The maximum number of trailing bytes of stderr included for each test.
+const MAX_STDERR_BYTES: usize = 64 * 1024;
+
+/// The number of tests listed as the slowest within a Markdown summary.
+const SLOWEST_COUNT: usize = 10;
+
+/// The name of the section for tests outside of any section.
+const NO_SECTION: &str = "(no section)";
+
+/// The styles of the HTML report.
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 1100px; padding: 0 1em; color: #1f2328; }
+h1, h2, h3 { border-bottom: 1px solid #d0d7de; padding-bottom: .3em; }
+table.metadata td { padding: .2em 1em .2em 0; vertical-align: top; }
+table.metadata td:first-child { font-weight: 600; white-space: nowrap; }
+code, pre { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 85%; }
+pre { background: #f6f8fa; border-radius: 6px; padding: .8em; overflow-x: auto; }
+details.test { border: 1px solid #d0d7de; border-radius: 6px; margin: .4em 0; padding: .4em .8em; }
+details.test > summary { cursor: pointer; }
+details.test[open] > summary { margin-bottom: .5em; }
+.badge { border-radius: 1em; color: #fff; display: inline-block; font-size: 75%; font-weight: 600; min-width: 3em; padding: .1em .6em; text-align: center; }
+.pass { background: #1a7f37; }
+.fail { background: #cf222e; }
+.skip { background: #9a6700; }
+.reason, .time { color: #59636e; }
+.counts .badge { margin-right: .3em; }
+"#;
+
+/// The record of a conformance test within a report.
+#[derive(Debug)]
+pub(crate) struct Record {
+    /// The name of the test.
+    name: String,
+
+    /// The heading of the section of the specification containing the test.
+    section: Option<String>,
+
+    /// The line of the specification on which the test starts.
+    line: Option<usize>,
+
+    /// The result of the test.
+    result: TestResult,
+
+    /// The time it took to execute the test, if it was executed.
+    elapsed: Option<Duration>,
+
+    /// The resources used by the execution, if measured.
+    usage: Option<ResourceUsage>,
+
+    /// The WDL source of the test.
+    src: String,
+
+    /// The example input.
+    input: Option<Value>,
+
+    /// The expected output.
+    expected_output: Option<Value>,
+
+    /// The outputs produced by the engine.
+    actual_output: Option<Value>,
+
+    /// The stderr of the execution.
+    stderr: String,
+}
+
+impl Record {
+    /// Creates a record for a test.
+    pub(crate) fn new(
+        test: &Test,
+        result: TestResult,
+        elapsed: Option<Duration>,
+        execution: Option<&Execution>,
+    ) -> Self {
+        Self {
+            name: test.file_name().to_string(),
+            section: test.section().map(ToOwned::to_owned),
+            line: test.line(),
+            result,
+            elapsed,
+            usage: execution.and_then(Execution::usage).copied(),
+            src: test.src().trim().to_string(),
+            input: test.input().cloned(),
+            expected_output: test.output().cloned(),
+            actual_output: execution.and_then(Execution::outputs).cloned(),
+            stderr: execution
+                .map(|execution| String::from_utf8_lossy(execution.stderr()).into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Gets the time it took to execute the test, if it was executed.
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+}
+
+/// The results of running a suite of conformance tests.
+#[derive(Debug)]
+pub(crate) struct Suite {
+    /// The name of the suite (e.g., the reference of the specification).
+    name: String,
+
+    /// The commit of the specification, if checked out from git.
+    commit: Option<Oid>,
+
+    /// The URL of the specification file, to which `#L<line>` is appended to
+    /// link to each test.
+    source_url: Option<String>,
+
+    /// The records of the tests.
+    records: Vec<Record>,
+}
+
+impl Suite {
+    /// Creates a new suite.
+    pub(crate) fn new(name: String, commit: Option<Oid>, source_url: Option<String>, records: Vec<Record>) -> Self {
+        Self {
+            name,
+            commit,
+            source_url,
+            records,
+        }
+    }
+
+    /// Gets the name of the suite.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the records of the tests.
+    pub(crate) fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Gets the number of tests that passed.
+    pub(crate) fn passed(&self) -> usize {
+        self.records.iter().filter(|r| r.result.is_passed()).count()
+    }
+
+    /// Gets the number of tests that failed.
+    pub(crate) fn failed(&self) -> usize {
+        self.records.iter().filter(|r| r.result.is_failed()).count()
+    }
+
+    /// Gets the number of tests that were skipped.
+    pub(crate) fn skipped(&self) -> usize {
+        self.records.iter().filter(|r| r.result.is_skipped()).count()
+    }
+
+    /// Gets the total time spent executing tests.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.records.iter().filter_map(Record::elapsed).sum()
+    }
+
+    /// Groups the records by the section of the specification containing
+    /// them, in the order in which the sections first appear.
+    fn sections(&self) -> Vec<(&str, Vec<&Record>)> {
+        let mut sections: Vec<(&str, Vec<&Record>)> = Vec::new();
+
+        for record in &self.records {
+            let section = record.section.as_deref().unwrap_or(NO_SECTION);
+
+            match sections.iter_mut().find(|(name, _)| *name == section) {
+                Some((_, records)) => records.push(record),
+                None => sections.push((section, vec![record])),
+            }
+        }
+
+        sections
+    }
+}
+
+/// Gets the URL of the specification file within a web view of the
+/// repository at a commit.
+///
+/// Only repositories hosted on GitHub are supported.
+pub(crate) fn source_url(url: &str, commit: Oid, file_name: &str) -> Option<String> {
+    let repository = url.strip_prefix("https://github.com/")?;
+    let repository = repository.trim_end_matches('/').trim_end_matches(".git");
+    Some(format!("https://github.com/{repository}/blob/{commit}/{file_name}"))
+}
+
+/// Writes a self-contained HTML report of the suites.
+///
+/// The metadata (e.g., the engine options) is listed at the top of the
+/// report.
+pub(crate) fn write_html(path: &Path, suites: &[&Suite], metadata: &[(&str, String)]) -> Result<()> {
+    let mut html = String::new();
+
+    // NOTE: writing to a `String` cannot fail.
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>WDL conformance report</title>");
+    let _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>");
+    let _ = writeln!(html, "<h1>WDL conformance report</h1>");
+
+    let _ = writeln!(html, "<table class=\"metadata\">");
+    for (name, value) in metadata {
+        let _ = writeln!(html, "<tr><td>{}</td><td><code>{}</code></td></tr>", escape(name), escape(value));
+    }
+    let _ = writeln!(html, "</table>");
+
+    for suite in suites {
+        write_suite(&mut html, suite);
+    }
+
+    let _ = writeln!(html, "</body>\n</html>");
+
+    std::fs::write(path, html).with_context(|| format!("writing HTML report to `{}`", path.display()))
+}
+
+/// Writes the results of a suite to an HTML report.
+fn write_suite(html: &mut String, suite: &Suite) {
+    match suite.commit {
+        Some(commit) => {
+            let _ = writeln!(
+                html,
+                "<h2>Specification <code>{}</code> at commit <code>{}</code></h2>",
+                escape(&suite.name),
+                commit
+            );
+        }
+        None => {
+            let _ = writeln!(html, "<h2>{}</h2>", escape(&suite.name));
+        }
+    }
+
+    let _ = writeln!(
+        html,
+        "<p class=\"counts\"><span class=\"badge pass\">{} passed</span><span class=\"badge fail\">{} \
+         failed</span><span class=\"badge skip\">{} skipped</span> in {:.2}s</p>",
+        suite.passed(),
+        suite.failed(),
+        suite.skipped(),
+        suite.elapsed().as_secs_f64()
+    );
+
+    for (section, records) in suite.sections() {
+        let passed = records.iter().filter(|r| r.result.is_passed()).count();
+        let _ = writeln!(html, "<h3>{} <small>({}/{} passed)</small></h3>", escape(section), passed, records.len());
+
+        for record in records {
+            write_record(html, suite, record);
+        }
+    }
+}
+
+/// Writes the record of a test to an HTML report.
+fn write_record(html: &mut String, suite: &Suite, record: &Record) {
+    let (class, status, reason) = match &record.result {
+        TestResult::Passed => ("pass", "PASS", None),
+        TestResult::Failed(reason) => ("fail", "FAIL", Some(reason.to_string())),
+        TestResult::Skipped(reason) => ("skip", "SKIP", Some(reason.to_string())),
+    };
+
+    let _ = write!(
+        html,
+        "<details class=\"test\"><summary><span class=\"badge {class}\">{status}</span> <code>{}</code>",
+        escape(&record.name)
+    );
+
+    if let Some(elapsed) = record.elapsed {
+        let _ = write!(html, " <span class=\"time\">[{:.2}s]</span>", elapsed.as_secs_f64());
+    }
+
+    if let Some(reason) = reason {
+        let _ = write!(html, " <span class=\"reason\">{}</span>", escape(&reason));
+    }
+
+    let _ = writeln!(html, "</summary>");
+
+    match (&suite.source_url, record.line) {
+        (Some(url), Some(line)) => {
+            let _ = writeln!(
+                html,
+                "<p><a href=\"{}#L{line}\">View in the specification (line {line})</a></p>",
+                escape(url)
+            );
+        }
+        (None, Some(line)) => {
+            let _ = writeln!(html, "<p>Line {line} of the specification</p>");
+        }
+        _ => {}
+    }
+
+    if let Some(usage) = &record.usage {
+        let _ = writeln!(html, "<p class=\"time\">{}</p>", escape(&usage.to_string()));
+    }
+
+    write_block(html, "Source", Some(&record.src));
+    write_block(html, "Input", record.input.as_ref().map(pretty).as_deref());
+    write_block(html, "Expected output", record.expected_output.as_ref().map(pretty).as_deref());
+    write_block(html, "Actual output", record.actual_output.as_ref().map(pretty).as_deref());
+
+    let stderr = tail(&record.stderr, MAX_STDERR_BYTES);
+    write_block(html, "Stderr", Some(stderr).filter(|s| !s.trim().is_empty()));
+
+    let _ = writeln!(html, "</details>");
+}
+
+/// Writes a GitHub-flavored Markdown summary of the suites.
+///
+/// The summary is meant to be appended to a CI job summary (e.g.,
+/// `$GITHUB_STEP_SUMMARY`), so it contains no ANSI escape codes and is
+/// appended to the file if it already exists.
+pub(crate) fn write_markdown(path: &Path, suites: &[&Suite]) -> Result<()> {
+    let mut markdown = String::new();
+
+    for suite in suites {
+        write_suite_markdown(&mut markdown, suite);
+    }
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(markdown.as_bytes()))
+        .with_context(|| format!("writing Markdown summary to `{}`", path.display()))
+}
+
+/// Writes the results of a suite to a Markdown summary.
+fn write_suite_markdown(markdown: &mut String, suite: &Suite) {
+    let icon = if suite.failed() > 0 { "❌" } else { "✅" };
+
+    match suite.commit {
+        Some(commit) => {
+            let _ = writeln!(
+                markdown,
+                "## {icon} WDL conformance: `{}` at commit `{}`\n",
+                suite.name,
+                &commit.to_string()[..7]
+            );
+        }
+        None => {
+            let _ = writeln!(markdown, "## {icon} WDL conformance: `{}`\n", suite.name);
+        }
+    }
+
+    // (1) The totals
+    let _ = writeln!(markdown, "| Passed | Failed | Skipped | Total | Time |");
+    let _ = writeln!(markdown, "|---:|---:|---:|---:|---:|");
+    let _ = writeln!(
+        markdown,
+        "| {} | {} | {} | {} | {:.2}s |\n",
+        suite.passed(),
+        suite.failed(),
+        suite.skipped(),
+        suite.records.len(),
+        suite.elapsed().as_secs_f64()
+    );
+
+    // (2) The failures with their reasons
+    let failures = suite
+        .records
+        .iter()
+        .filter_map(|record| match &record.result {
+            TestResult::Failed(reason) => Some((record, reason)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if !failures.is_empty() {
+        let _ = writeln!(markdown, "### Failures ({})\n", failures.len());
+        let _ = writeln!(markdown, "| Test | Reason |");
+        let _ = writeln!(markdown, "|---|---|");
+
+        for (record, reason) in failures {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} |",
+                test_link(suite, record),
+                escape_cell(&reason.to_string())
+            );
+        }
+
+        let _ = writeln!(markdown);
+    }
+
+    // (3) The skipped tests grouped by the reason they were skipped
+    let mut skipped: Vec<(&SkipReason, Vec<&Record>)> = Vec::new();
+    for record in &suite.records {
+        if let TestResult::Skipped(reason) = &record.result {
+            match skipped.iter_mut().find(|(r, _)| *r == reason) {
+                Some((_, records)) => records.push(record),
+                None => skipped.push((reason, vec![record])),
+            }
+        }
+    }
+
+    if !skipped.is_empty() {
+        let _ = writeln!(markdown, "### Skipped ({})\n", suite.skipped());
+
+        for (reason, records) in skipped {
+            let names = records
+                .iter()
+                .map(|record| format!("`{}`", record.name))
+                .collect::<Vec<_>>();
+
+            let _ = writeln!(markdown, "- **{}** ({}): {}", reason, records.len(), names.join(", "));
+        }
+
+        let _ = writeln!(markdown);
+    }
+
+    // (4) The slowest tests
+    let mut executed = suite
+        .records
+        .iter()
+        .filter_map(|record| record.elapsed.map(|elapsed| (record, elapsed)))
+        .collect::<Vec<_>>();
+
+    if !executed.is_empty() {
+        executed.sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+
+        let _ = writeln!(markdown, "### Slowest tests\n");
+        let _ = writeln!(markdown, "| Test | Time |");
+        let _ = writeln!(markdown, "|---|---:|");
+
+        for (record, elapsed) in executed.into_iter().take(SLOWEST_COUNT) {
+            let _ = writeln!(markdown, "| {} | {:.2}s |", test_link(suite, record), elapsed.as_secs_f64());
+        }
+
+        let _ = writeln!(markdown);
+    }
+}
+
+/// Gets the name of a test within a Markdown summary, linked to the test
+/// within the specification when possible.
+fn test_link(suite: &Suite, record: &Record) -> String {
+    match (&suite.source_url, record.line) {
+        (Some(url), Some(line)) => format!("[`{}`]({url}#L{line})", record.name),
+        _ => format!("`{}`", record.name),
+    }
+}
+
+/// Escapes text for use within a cell of a Markdown table.
+fn escape_cell(s: &str) -> String {
+    s.trim().replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Writes a titled block of preformatted text to an HTML report, if present.
+fn write_block(html: &mut String, title: &str, contents: Option<&str>) {
+    if let Some(contents) = contents {
+        let _ = writeln!(html, "<h4>{title}</h4>\n<pre>{}</pre>", escape(contents));
+    }
+}
+
+/// Pretty prints a JSON value.
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// Gets the last bytes of a string, starting at a character boundary.
+fn tail(s: &str, max: usize) -> &str {
+    let mut start = s.len().saturating_sub(max);
+
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+
+    &s[start..]
+}
+
+/// Escapes text for use within HTML.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::test::example;
+
+    #[test]
+    fn source_urls() {
+        let commit = Oid::from_str("4220a3e1ad8c6287cd9857674ddd8b5459bdac58").unwrap();
+
+        assert_eq!(
+            source_url("https://github.com/openwdl/wdl.git", commit, "SPEC.md").unwrap(),
+            "https://github.com/openwdl/wdl/blob/4220a3e1ad8c6287cd9857674ddd8b5459bdac58/SPEC.md"
+        );
+        assert!(source_url("file:///srv/mirrors/wdl.git", commit, "SPEC.md").is_none());
+    }
+
+    #[test]
+    fn writes_markdown() {
+        let tests = crate::conformance::Tests::compile(
+            ["a.wdl", "b.wdl", "c.wdl"]
+                .map(|name| example(name, "version 1.2", Some("{}"), None))
+                .concat(),
+        )
+        .unwrap();
+        let tests = tests.tests().collect::<Vec<_>>();
+
+        let failure = crate::conformance::FailureReason::ExecutionError(String::from("a | b\nc"));
+        let records = vec![
+            Record::new(tests[0], TestResult::Failed(failure), Some(Duration::from_secs(2)), None),
+            Record::new(tests[1], TestResult::Skipped(SkipReason::Ignored), None, None),
+            Record::new(tests[2], TestResult::Skipped(SkipReason::Ignored), None, None),
+        ];
+        let suite = Suite::new(String::from("wdl-1.2"), None, None, records);
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("summary.md");
+        std::fs::write(&path, "# Earlier step\n\n").unwrap();
+        write_markdown(&path, &[&suite]).unwrap();
+
+        let markdown = std::fs::read_to_string(path).unwrap();
+        assert!(markdown.starts_with("# Earlier step\n\n## ❌ WDL conformance: `wdl-1.2`"));
+        assert!(markdown.contains("| 0 | 1 | 2 | 3 | 2.00s |"));
+        assert!(markdown.contains("| `a.wdl` | execution error: a \\| b<br>c |"));
+        assert!(markdown.contains("- **test marked with `ignore: true`** (2): `b.wdl`, `c.wdl`"));
+        assert!(markdown.contains("| `a.wdl` | 2.00s |"));
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(escape(r#"<a href="x">&'"#), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;");
+        assert_eq!(tail("héllo", 4), "llo");
+    }
+}
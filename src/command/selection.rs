@@ -0,0 +1,104 @@
+use bon::Builder;
+
+use crate::conformance::Capability;
+use crate::conformance::SkipReason;
+use crate::conformance::Test;
+
+/// How a conformance test is treated within a run.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Selection {
+    /// The test is filtered out by its name and is not reported.
+    Excluded,
+
+    /// The test is reported as skipped.
+    Skipped(SkipReason),
+
+    /// The test is run.
+    Selected,
+}
+
+/// Selects the conformance tests to run.
+#[derive(Builder, Debug)]
+#[builder(builder_type = Builder)]
+pub(crate) struct Selector<'a> {
+    /// The patterns of which a test name must contain at least one, if any.
+    #[builder(default)]
+    include: &'a [String],
+
+    /// The patterns of which a test name must contain none.
+    #[builder(default)]
+    exclude: &'a [String],
+
+    /// The available capabilities.
+    ///
+    /// `None` means that every capability is available.
+    capabilities: Option<&'a [Capability]>,
+}
+
+impl Selector<'_> {
+    /// Selects whether a test is run.
+    ///
+    /// Tests are filtered by their name (without `.wdl`) before those that
+    /// are ignored or require missing capabilities are skipped.
+    pub(crate) fn select(&self, test: &Test) -> Selection {
+        let name = test.file_name().trim_end_matches(".wdl");
+        if !self.include.is_empty() && !self.include.iter().any(|pattern| name.contains(pattern.as_str())) {
+            return Selection::Excluded;
+        }
+        if self.exclude.iter().any(|pattern| name.contains(pattern.as_str())) {
+            return Selection::Excluded;
+        }
+
+        if test.config().ignore() {
+            return Selection::Skipped(SkipReason::Ignored);
+        }
+
+        let missing_capabilities = test
+            .config()
+            .capabilities()
+            .iter()
+            .filter(|cap| self.capabilities.is_some_and(|capabilities| !capabilities.contains(cap)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !missing_capabilities.is_empty() {
+            return Selection::Skipped(SkipReason::MissingCapabilities(missing_capabilities));
+        }
+
+        Selection::Selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::test::Config;
+
+    fn test(name: &str, config: &str) -> Test {
+        Test::builder()
+            .file_name(name.to_string())
+            .src(String::from("version 1.2"))
+            .config(serde_json::from_str::<Config>(config).unwrap())
+            .build()
+    }
+
+    #[test]
+    fn selects_tests() {
+        let include = [String::from("array")];
+        let selector = Selector::builder().include(&include).capabilities(&[]).build();
+
+        assert_eq!(selector.select(&test("map.wdl", "{}")), Selection::Excluded);
+        assert_eq!(selector.select(&test("array.wdl", "{}")), Selection::Selected);
+        assert_eq!(
+            selector.select(&test("array_ignored.wdl", r#"{"ignore": true}"#)),
+            Selection::Skipped(SkipReason::Ignored)
+        );
+        assert_eq!(
+            selector.select(&test("array_gpu.wdl", r#"{"capabilities": ["gpu"]}"#)),
+            Selection::Skipped(SkipReason::MissingCapabilities(vec![Capability::Gpu]))
+        );
+
+        let selector = Selector::builder().build();
+        assert_eq!(selector.select(&test("array_gpu.wdl", r#"{"capabilities": ["gpu"]}"#)), Selection::Selected);
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::bail;
+use anyhow::Context as _;
+use anyhow::Result;
+use bon::builder;
+use git2::Oid;
+
+use crate::cache::Cache;
+use crate::conformance::Document;
+use crate::Repository;
+
+/// The file name of the specification.
+pub(crate) const SPEC_FILE_NAME: &str = "SPEC.md";
+
+/// The name of a specification read from stdin.
+pub(crate) const STDIN_DOCUMENT_NAME: &str = "stdin";
+
+/// Checks out a reference of the specification repository and reads the
+/// specification.
+///
+/// Without a local directory, the checkout is cached within the user's cache
+/// directory. A temporary directory (e.g., on platforms without a cache
+/// directory) is removed once the specification is read.
+///
+/// Returns the contents of the specification and the commit that was checked
+/// out.
+#[builder]
+pub(crate) fn read(
+    url: &str,
+    reference: &str,
+    local_dir: Option<&Path>,
+    #[builder(default)] update: bool,
+    #[builder(default)] offline: bool,
+) -> Result<(String, Oid)> {
+    let cache_dir = match local_dir {
+        Some(_) => None,
+        None => Cache::user().map(|cache| cache.entry_dir(url, reference)),
+    };
+
+    let spec_tempdir = match (local_dir, &cache_dir) {
+        (None, None) => Some(tempfile::tempdir().context("creating temporary directory for the specification")?),
+        _ => None,
+    };
+
+    let local_dir = local_dir
+        .map(Path::to_path_buf)
+        .or(cache_dir)
+        .or_else(|| spec_tempdir.as_ref().map(|dir| dir.path().join("wdl")));
+
+    let checkout = Repository::builder()
+        .reference(reference)
+        .url(url)
+        .maybe_local_dir(local_dir)
+        .update(update)
+        .offline(offline)
+        .build()
+        .checkout()?;
+
+    let spec = checkout.path().join(SPEC_FILE_NAME);
+
+    if !spec.exists() {
+        bail!(
+            "the specification does not exist at `{}` in the git repository",
+            SPEC_FILE_NAME
+        );
+    }
+
+    let contents = std::fs::read_to_string(&spec).with_context(|| format!("reading `{}`", spec.display()))?;
+    Ok((contents, checkout.commit()))
+}
+
+/// Reads the specification files outside of a git repository.
+///
+/// A path of `-` reads a specification from stdin, and paths that do not
+/// exist are expanded as globs. Files matched more than once are only read
+/// once.
+pub(crate) fn read_files(paths: &[PathBuf]) -> Result<Vec<Document>> {
+    let mut documents = Vec::new();
+    let mut seen = Vec::new();
+
+    for path in paths {
+        if path == Path::new("-") {
+            if documents.iter().any(|document: &Document| document.name() == STDIN_DOCUMENT_NAME) {
+                bail!("the specification may only be read from stdin once");
+            }
+
+            let contents =
+                std::io::read_to_string(std::io::stdin()).context("reading the specification from stdin")?;
+            documents.push(Document::new(STDIN_DOCUMENT_NAME, contents));
+            continue;
+        }
+
+        let matches = if path.exists() {
+            vec![path.clone()]
+        } else {
+            let pattern = path.to_str().context("specification file paths must be UTF-8")?;
+            let mut matches = glob::glob(pattern)
+                .with_context(|| format!("invalid specification file pattern `{pattern}`"))?
+                .collect::<Result<Vec<_>, _>>()?;
+            matches.sort();
+            matches
+        };
+
+        if matches.is_empty() {
+            bail!("no specification files match `{}`", path.display());
+        }
+
+        for path in matches {
+            // NOTE: a file matched several times (e.g., by a glob and by its
+            // path) is only read once.
+            let canonical = path
+                .canonicalize()
+                .with_context(|| format!("resolving specification file `{}`", path.display()))?;
+            if seen.contains(&canonical) {
+                continue;
+            }
+            seen.push(canonical);
+
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("reading specification file `{}`", path.display()))?;
+            documents.push(Document::new(path.display().to_string(), contents));
+        }
+    }
+
+    Ok(documents)
+}
@@ -1,34 +1,111 @@
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
-use std::process::Stdio;
+use std::time::Duration;
 
 use anyhow::bail;
 use anyhow::Context as _;
 use anyhow::Result;
+use bon::builder;
 use clap::Parser;
-use strum::IntoEnumIterator;
-
-use crate::conformance::test::validation::validate_outputs;
+use git2::Oid;
+
+use crate::command::engine::prepare_workdir;
+use crate::command::engine::EngineArgs;
+use crate::command::probe;
+use crate::command::report;
+use crate::command::report::Record;
+use crate::command::report::Suite;
+use crate::command::selection::Selection;
+use crate::command::selection::Selector;
+use crate::command::specification;
+use crate::command::specification::SPEC_FILE_NAME;
+use crate::command::specification::STDIN_DOCUMENT_NAME;
 use crate::conformance::test::Runner;
 use crate::conformance::Capability;
-use crate::conformance::FailureReason;
-use crate::conformance::ReturnCode;
-use crate::conformance::SkipReason;
+use crate::conformance::CommandEngine;
+use crate::conformance::Document;
+use crate::conformance::Execution;
+use crate::conformance::ResourceUsage;
 use crate::conformance::Test;
 use crate::conformance::TestResult;
-use crate::shell::substitute;
-use crate::Repository;
+use crate::repository::REPOSITORY_URL;
+
+/// The file name of the stdout log for each test.
+const STDOUT_LOG_FILE_NAME: &str = "stdout.log";
+
+/// The file name of the stderr log for each test.
+const STDERR_LOG_FILE_NAME: &str = "stderr.log";
+
+/// The file name of the execution log for each test.
+const EXECUTION_LOG_FILE_NAME: &str = "execution.json";
+
+/// Which per-test working directories to keep after the run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum KeepWorkdirs {
+    /// Keep the working directories of all executed tests.
+    All,
+    /// Keep the working directories of failed tests only.
+    Failed,
+}
+
+/// A value of the `--capabilities` argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CapabilitiesArg {
+    /// Detect the capabilities by probing the engine.
+    Auto,
+    /// A capability available for tests.
+    Capability(Capability),
+}
+
+/// Parses a value of the `--capabilities` argument.
+fn parse_capabilities_arg(s: &str) -> Result<CapabilitiesArg, String> {
+    if s == "auto" {
+        return Ok(CapabilitiesArg::Auto);
+    }
 
-/// The file name of the specification.
-const SPEC_FILE_NAME: &str = "SPEC.md";
+    s.parse().map(CapabilitiesArg::Capability)
+}
 
 /// Performs conformance tests on the WDL specification.
 #[derive(Parser, Debug)]
 pub struct Args {
     /// The branch to check out.
-    #[arg(short, long, default_value = "wdl-1.2")]
-    branch: String,
+    ///
+    /// This may also be a tag, a full or abbreviated commit SHA, or a fully
+    /// qualified reference such as `refs/pull/N/head`. Several references
+    /// may be given (comma-separated) to run the suite of each, in which
+    /// case each suite is compiled and its results are written within a
+    /// subdirectory named after the reference.
+    #[arg(
+        short,
+        long = "branch",
+        value_name = "BRANCH",
+        value_delimiter = ',',
+        default_value = "wdl-1.2"
+    )]
+    branches: Vec<String>,
+
+    /// The URL of the specification repository.
+    ///
+    /// This is useful for testing against forks or `file://` mirrors.
+    #[arg(long, default_value = REPOSITORY_URL)]
+    url: String,
+
+    /// A markdown file containing the conformance tests (`-` for stdin).
+    ///
+    /// The file is read directly rather than checking out the
+    /// specification repository, so it may be any markdown document, such
+    /// as an uncommitted copy of `SPEC.md`. This may be repeated or be a glob
+    /// (e.g., `'docs/**/*.md'`) to compile the tests from every document
+    /// into one suite. Tests with the same name in different documents are
+    /// prefixed with the file stem of their document (e.g.,
+    /// `extensions-hello.wdl`).
+    #[arg(
+        long = "spec-file",
+        value_name = "PATH",
+        conflicts_with_all = ["branches", "url", "specification_dir", "update", "offline"]
+    )]
+    spec_files: Vec<PathBuf>,
 
     /// A directory that contains the conformance tests.
     #[arg(short, long)]
@@ -39,36 +116,36 @@ pub struct Args {
     force: bool,
 
     /// A directory that contains the specification repository.
+    ///
+    /// If not provided, the specification is checked out within the user's
+    /// cache directory and reused by later runs (see `spectool cache`).
     #[arg(short, long)]
     specification_dir: Option<PathBuf>,
 
-    /// Runtime capabilities available for tests (comma-separated).
-    ///
-    /// Tests requiring capabilities not in this list will be skipped.
-    #[arg(long, value_delimiter = ',', conflicts_with = "all_capabilities")]
-    capabilities: Vec<Capability>,
+    /// Whether to fetch and fast-forward an existing specification directory.
+    #[arg(long, default_value_t = false)]
+    update: bool,
 
-    /// Enable all runtime capabilities.
-    #[arg(long, conflicts_with = "capabilities")]
-    all_capabilities: bool,
+    /// Whether to use the existing specification directory (or the cached
+    /// specification) without accessing the network.
+    #[arg(long, default_value_t = false, conflicts_with = "update")]
+    offline: bool,
 
-    /// Arguments to append when running a workflow.
+    /// Runtime capabilities available for tests (comma-separated).
     ///
-    /// Use `~{target}` for the workflow name.
-    #[arg(long, default_value = "")]
-    workflow_target_args: String,
-
-    /// Arguments to append when running a task.
+    /// Tests requiring capabilities not in this list will be skipped. If
+    /// `auto`, the capabilities are detected by probing the engine (see
+    /// `spectool probe`).
     ///
-    /// Use `~{target}` for the task name.
-    #[arg(long, default_value = "")]
-    task_target_args: String,
+    /// Known capabilities are `cpu`, `memory`, `gpu`, `disks`, and
+    /// `allow_nested_inputs`. Custom capabilities are namespaced (e.g.,
+    /// `sprocket:network`).
+    #[arg(long, value_delimiter = ',', value_parser = parse_capabilities_arg, conflicts_with = "all_capabilities")]
+    capabilities: Vec<CapabilitiesArg>,
 
-    /// Redirect stdout to the outputs file.
-    ///
-    /// If enabled, appends `> ~{output}` at the end of the command.
-    #[arg(long, default_value_t = false)]
-    redirect_stdout: bool,
+    /// Enable all runtime capabilities, including custom ones.
+    #[arg(long, conflicts_with = "capabilities")]
+    all_capabilities: bool,
 
     /// Only run tests matching these patterns (comma-separated).
     ///
@@ -84,191 +161,416 @@ pub struct Args {
     #[arg(long, value_delimiter = ',', conflicts_with = "include")]
     exclude: Vec<String>,
 
-    /// A `jq` selector to apply to `outputs.json` before validation.
+    /// A directory to write the results of the run to.
     ///
-    /// This allows transforming the output JSON before comparing against expected output.
-    /// For example, `--output-selector '.outputs'` will extract the `outputs` field from the output.
+    /// The stdout, stderr, command, exit code, and timing of each executed
+    /// test are written to `logs/<test>/` within this directory.
+    #[arg(long, value_name = "DIR")]
+    results_dir: Option<PathBuf>,
+
+    /// Keep the working directory of each test after the run.
     ///
-    /// Uses `jq` syntax (e.g., `'.outputs'`, `'.result.data[0]'`, etc.).
+    /// Each test is executed within its own working directory containing the
+    /// inputs, outputs, data, and any artifacts created by the engine. These
+    /// are kept within `workdirs/<test>/` of the `--results-dir` (or a
+    /// temporary directory if none is provided). By default, all working
+    /// directories are removed.
+    #[arg(long, value_name = "WHICH", num_args = 0..=1, default_missing_value = "all")]
+    keep_workdirs: Option<KeepWorkdirs>,
+
+    /// The number of trailing stderr lines to print for each failed test.
+    #[arg(long, value_name = "LINES", default_value_t = 10)]
+    stderr_tail: usize,
+
+    /// Show the CPU time and peak memory of each test next to its elapsed
+    /// time, and summarize the heaviest tests after the run.
+    ///
+    /// Resource usage is only measured on Unix platforms.
     #[arg(long)]
-    output_selector: Option<String>,
+    show_usage: bool,
 
-    /// WDL version to inject into test files.
-    ///
-    /// Replaces the `version` statement in each test file before writing to disk.
-    /// For example, `--inject-wdl-version development` will replace `version 1.2`
-    /// with `version development`.
+    /// Write a self-contained HTML report of the run to this path.
     ///
-    /// This is useful when testing against engines that require specific version strings.
-    #[arg(long, value_name = "VERSION")]
-    inject_wdl_version: Option<String>,
+    /// The report groups the tests by the section of the specification
+    /// containing them and includes the source, inputs, expected and actual
+    /// outputs, and stderr of each test.
+    #[arg(long, value_name = "PATH")]
+    report_html: Option<PathBuf>,
 
-    /// The command to call for each execution.
+    /// Append a GitHub-flavored Markdown summary of the run to this path.
     ///
-    #[arg(help = r#"The command to call for each execution.
+    /// The summary lists the totals, the failed tests with their reasons,
+    /// the skipped tests grouped by reason, and the slowest tests. It may be
+    /// written directly to a CI job summary (e.g., `$GITHUB_STEP_SUMMARY`).
+    #[arg(long, value_name = "PATH")]
+    summary_markdown: Option<PathBuf>,
+
+    /// The arguments for the engine.
+    #[command(flatten)]
+    engine: EngineArgs,
+}
 
-The following substitutions are supported:
+pub fn main(args: Args) -> Result<()> {
+    //===================//
+    // Set up the engine //
+    //===================//
 
-  - `~{path}` is the path to the file.
-  - `~{input}` is the path to the inputs.json file.
-  - `~{output}` is the path to the outputs.json file."#)]
-    command: String,
-}
+    let engine = args.engine.engine()?;
 
-pub fn main(mut args: Args) -> Result<()> {
     //======================//
     // Handle capabilities //
     //======================//
 
-    if args.all_capabilities {
-        args.capabilities = Capability::iter().collect();
+    // NOTE: `None` means that every capability is available.
+    let capabilities = if args.all_capabilities {
+        None
+    } else if args.capabilities.contains(&CapabilitiesArg::Auto) {
+        if args.capabilities.len() > 1 {
+            bail!("`--capabilities auto` cannot be combined with other capabilities");
+        }
+
+        let probes = probe::run_probes(&args.engine, &engine)?;
+        if let Some(mismatch) = probes.version_mismatch() {
+            eprintln!("warning: no capabilities were detected because {mismatch}");
+        }
+
+        let capabilities = probes.supported();
+        println!(
+            "Detected capabilities: {}\n",
+            if capabilities.is_empty() {
+                "none".to_string()
+            } else {
+                capabilities.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            }
+        );
+        Some(capabilities)
+    } else {
+        let capabilities = args
+            .capabilities
+            .iter()
+            .filter_map(|arg| match arg {
+                CapabilitiesArg::Capability(capability) => Some(capability.clone()),
+                CapabilitiesArg::Auto => None,
+            })
+            .collect::<Vec<_>>();
+
+        for lint in capabilities.iter().filter_map(Capability::lint) {
+            eprintln!("warning: {lint}");
+        }
+
+        Some(capabilities)
+    };
+
+    //======================================================//
+    // Set up the conformance tests and working directories //
+    //======================================================//
+
+    let root_tempdir = match args.conformance_test_dir {
+        Some(_) => None,
+        None => Some(tempfile::tempdir().context("creating temporary directory for the conformance tests")?),
+    };
+
+    let root_dir = match &args.conformance_test_dir {
+        Some(path) => std::fs::canonicalize(path).expect("path to canonicalize"),
+        // SAFETY: the temporary directory is always created when no
+        // conformance tests directory is provided.
+        None => root_tempdir.as_ref().unwrap().path().to_path_buf(),
+    };
+
+    let workdirs_tempdir = match args.results_dir {
+        Some(_) => None,
+        None => Some(tempfile::tempdir().context("creating temporary directory for working directories")?),
+    };
+
+    // NOTE: the working directories are kept within the results directory,
+    // if one is provided, and a temporary directory otherwise.
+    let dirs = |suite_dir: Option<&str>| {
+        let join = |dir: &Path| match suite_dir {
+            Some(suite_dir) => dir.join(suite_dir),
+            None => dir.to_path_buf(),
+        };
+
+        let results_dir = args.results_dir.as_deref().map(join);
+        let workdirs_dir = match &results_dir {
+            Some(results_dir) => results_dir.join("workdirs"),
+            // SAFETY: the temporary directory is always created when no
+            // results directory is provided.
+            None => join(workdirs_tempdir.as_ref().unwrap().path()),
+        };
+
+        (join(&root_dir), results_dir, workdirs_dir)
+    };
+
+    //================//
+    // Run the suites //
+    //================//
+
+    let mut summaries = Vec::new();
+
+    if args.spec_files.is_empty() {
+        // NOTE: with several branches, each suite is compiled, run, and
+        // logged within its own subdirectory.
+        let multiple = args.branches.len() > 1;
+
+        for (i, branch) in args.branches.iter().enumerate() {
+            if multiple {
+                if i > 0 {
+                    println!();
+                }
+
+                println!("{}", "#".repeat(60));
+                println!("Specification `{}`", branch);
+                println!("{}\n", "#".repeat(60));
+            }
+
+            let (contents, commit) = specification::read()
+                .url(&args.url)
+                .reference(branch)
+                .maybe_local_dir(args.specification_dir.as_deref())
+                .update(args.update)
+                .offline(args.offline)
+                .call()?;
+
+            println!("Using specification `{}` at commit {}\n", branch, commit);
+
+            let suite_dir = multiple.then(|| suite_dir_name(branch));
+            let (root_dir, results_dir, workdirs_dir) = dirs(suite_dir.as_deref());
+            let summary = run_suite()
+                .args(&args)
+                .engine(&engine)
+                .maybe_capabilities(capabilities.as_deref())
+                .name(branch.clone())
+                .commit(commit)
+                .maybe_source_url(report::source_url(&args.url, commit, SPEC_FILE_NAME))
+                .documents(&[Document::new(SPEC_FILE_NAME, contents)])
+                .root_dir(root_dir)
+                .maybe_results_dir(results_dir)
+                .workdirs_dir(workdirs_dir)
+                .call()?;
+
+            summaries.push(summary);
+        }
+    } else {
+        let documents = specification::read_files(&args.spec_files)?;
+
+        for document in &documents {
+            match document.name() {
+                STDIN_DOCUMENT_NAME => println!("Using specification from stdin"),
+                name => println!("Using specification file `{}`", name),
+            }
+        }
+
+        println!();
+        let (root_dir, results_dir, workdirs_dir) = dirs(None);
+        let name = documents.iter().map(Document::name).collect::<Vec<_>>().join(", ");
+        let summary = run_suite()
+            .args(&args)
+            .engine(&engine)
+            .maybe_capabilities(capabilities.as_deref())
+            .name(name)
+            .documents(&documents)
+            .root_dir(root_dir)
+            .maybe_results_dir(results_dir)
+            .workdirs_dir(workdirs_dir)
+            .call()?;
+
+        summaries.push(summary);
     }
 
-    //=======================================//
-    // Checkout the specification repository //
-    //=======================================//
+    let kept_workdirs = summaries.iter().map(|summary| summary.kept_workdirs).sum::<usize>();
+    if kept_workdirs > 0 {
+        // Persist the temporary directory so the kept working directories
+        // outlive the run
+        if let Some(tempdir) = workdirs_tempdir {
+            let _ = tempdir.keep();
+        }
+    }
 
-    let (_, path) = Repository::builder()
-        .branch(args.branch)
-        .maybe_local_dir(args.specification_dir)
-        .build()
-        .checkout()?;
+    if summaries.len() > 1 {
+        print_version_summary(&summaries);
+    }
 
-    //=================================//
-    // Read the specification contents //
-    //=================================//
+    //===================//
+    // Write the reports //
+    //===================//
 
-    let spec = path.join(SPEC_FILE_NAME);
+    if let Some(path) = &args.report_html {
+        let mut metadata = args.engine.metadata();
+        metadata.push(("Capabilities", match &capabilities {
+            Some(capabilities) if capabilities.is_empty() => String::from("none"),
+            Some(capabilities) => capabilities.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+            None => String::from("all"),
+        }));
+        metadata.push(("spectool version", env!("CARGO_PKG_VERSION").to_string()));
+
+        let suites = summaries.iter().map(|summary| &summary.suite).collect::<Vec<_>>();
+        report::write_html(path, &suites, &metadata)?;
+        println!("\nWrote HTML report to `{}`", path.display());
+    }
 
-    if !spec.exists() {
-        bail!(
-            "the specification does not exist at `{}` in the git repository",
-            SPEC_FILE_NAME
-        );
+    if let Some(path) = &args.summary_markdown {
+        let suites = summaries.iter().map(|summary| &summary.suite).collect::<Vec<_>>();
+        report::write_markdown(path, &suites)?;
+        println!("\nWrote Markdown summary to `{}`", path.display());
     }
 
-    let contents = std::fs::read_to_string(spec)?;
+    let failed = summaries.iter().map(|summary| summary.suite.failed()).sum::<usize>();
+    if failed > 0 {
+        bail!("{} test(s) failed", failed);
+    }
+
+    Ok(())
+}
+
+/// The summary of running a suite of conformance tests.
+#[derive(Debug)]
+struct Summary {
+    /// The results of the suite.
+    suite: Suite,
 
+    /// The number of working directories that were kept.
+    kept_workdirs: usize,
+}
+
+/// Compiles and runs a suite of conformance tests.
+///
+/// The results of each test and a summary of the suite are printed.
+#[builder]
+fn run_suite(
+    args: &Args,
+    engine: &CommandEngine,
+    capabilities: Option<&[Capability]>,
+    name: String,
+    commit: Option<Oid>,
+    source_url: Option<String>,
+    documents: &[Document],
+    root_dir: PathBuf,
+    results_dir: Option<PathBuf>,
+    workdirs_dir: PathBuf,
+) -> Result<Summary> {
     //===============================//
     // Compile the conformance tests //
     //===============================//
 
-    let root_dir = args
-        .conformance_test_dir
-        .map(|path| std::fs::canonicalize(path).expect("path to canonicalize"))
-        .unwrap_or_else(|| tempfile::tempdir().expect("tempdir to create").into_path());
-
-    let runner = Runner::compile(
+    let runner = Runner::compile_documents(
         root_dir,
-        contents,
+        documents,
         args.force,
-        args.inject_wdl_version.clone(),
+        args.engine.inject_wdl_version(),
     )?;
 
-    //===================================//
-    // Set up the test working directory //
-    //===================================//
+    for lint in runner.lints() {
+        eprintln!("warning: {lint}");
+    }
 
-    // SAFETY: this should create on all platforms we care about.
-    let workdir = tempfile::tempdir().expect("tempdir to create").into_path();
+    let mut kept_workdirs = 0;
 
     //===============//
     // Run the tests //
     //===============//
 
-    let mut results = Vec::new();
+    let mut records = Vec::new();
+    let mut usages = Vec::new();
     let mut total_elapsed = std::time::Duration::ZERO;
 
+    let selector = Selector::builder()
+        .include(&args.include)
+        .exclude(&args.exclude)
+        .maybe_capabilities(capabilities)
+        .build();
+
     for test in runner.tests() {
-        // (1) Check if test should be filtered by include/exclude
+        // (1) Check if test should be filtered, ignored, or skipped for
+        // missing capabilities
         let test_name = test.file_name().trim_end_matches(".wdl");
-        if !args.include.is_empty() && !args.include.iter().any(|pattern| test_name.contains(pattern.as_str())) {
-            continue;
-        }
-        if !args.exclude.is_empty() && args.exclude.iter().any(|pattern| test_name.contains(pattern.as_str())) {
-            continue;
-        }
-
-        // (2) Check if test should be ignored
-        if test.config().ignore() {
-            print_result(test.file_name(), "SKIP", Some("test marked with `ignore: true`"), None);
-            results.push((test.file_name().to_string(), TestResult::Skipped(SkipReason::Ignored)));
-            continue;
-        }
-
-        // (2) Check if test has required capabilities
-        let missing_capabilities: Vec<Capability> = test
-            .config()
-            .capabilities()
-            .iter()
-            .filter(|cap| !args.capabilities.contains(cap))
-            .cloned()
-            .collect();
-
-        if !missing_capabilities.is_empty() {
-            let reason = SkipReason::MissingCapabilities(missing_capabilities);
-            print_result(test.file_name(), "SKIP", Some(&reason.to_string()), None);
-            results.push((
-                test.file_name().to_string(),
-                TestResult::Skipped(reason),
-            ));
-            continue;
+        match selector.select(test) {
+            Selection::Excluded => continue,
+            Selection::Skipped(reason) => {
+                print_result(test.file_name(), "SKIP", Some(&reason.to_string()), None, None);
+                records.push(Record::new(test, TestResult::Skipped(reason), None, None));
+                continue;
+            }
+            Selection::Selected => {}
         }
 
-        // (3) Recreate the working directory to ensure it's empty
-        // SAFETY: we expect to be able to remove and recreate the directory on all
-        // platforms we care about within this subcommand.
-        std::fs::remove_dir_all(&workdir).unwrap();
-        std::fs::create_dir_all(&workdir).unwrap();
-
-        // (4) Copy data directory to the working directory
-        let source_data_dir = runner.root_dir().join("data");
-        let dest_data_dir = &workdir;
-        if source_data_dir.exists() {
-            let mut options = fs_extra::dir::CopyOptions::new();
-            options.overwrite = true;
-            options.copy_inside = true;
-            // SAFETY: we expect to be able to copy the `data` directory on all
-            // platforms we care about within this subcommand.
-            fs_extra::dir::copy(&source_data_dir, dest_data_dir, &options).unwrap();
-        }
+        // (2) Create the working directory for the test with a copy of the
+        // data directory
+        let workdir = workdirs_dir.join(test_name);
+        prepare_workdir(&runner.root_dir().join("data"), &workdir)?;
+
+        // (3) Create the inputs file
+        let input_file = match args.engine.create_input_json(test, &workdir) {
+            Ok(input_file) => input_file,
+            Err(reason) => {
+                let details = reason.to_string();
+                let result = TestResult::Failed(reason);
+                let log_dir = results_dir.as_ref().map(|results_dir| results_dir.join("logs").join(test_name));
+                if let Some(log_dir) = &log_dir {
+                    write_logs(log_dir, test, &result, None, None)?;
+                }
+
+                print_result(test.file_name(), "FAIL", Some(&details), None, None);
+                print_failure_logs(log_dir.as_deref(), None, args.stderr_tail);
+                records.push(Record::new(test, result, None, None));
+                continue;
+            }
+        };
 
-        // (5) Create the inputs file
-        let input_file = create_input_json(test, &workdir).unwrap();
-
-        // (5) Substitute the command
-        let target = test.target().expect("target should be inferred");
-        let output_file = workdir.join("outputs.json");
-        let command = substitute()
-            .command(args.command.clone())
-            .path(test.path().unwrap().to_path_buf())
-            .input(input_file)
-            .output(output_file)
-            .target(target.clone())
-            .workflow_target_args(args.workflow_target_args.clone())
-            .task_target_args(args.task_target_args.clone())
-            .call();
-
-        tracing::debug!("executing command `{}`", command);
-
-        // (6) Execute the test and evaluate the result
+        // (4) Execute the test and evaluate the result
         let start_time = std::time::Instant::now();
-        let result = execute_and_evaluate_test(test, &command, runner.root_dir(), &workdir, args.redirect_stdout, args.output_selector.as_deref());
+        let evaluation = args.engine.evaluate(test, engine, &input_file, &workdir);
         let elapsed = start_time.elapsed();
         total_elapsed += elapsed;
 
-        // (8) Print result and store it
-        match &result {
-            TestResult::Passed => print_result(test.file_name(), "PASS", None, Some(elapsed)),
+        // (5) Persist the logs of the test
+        let log_dir = results_dir.as_ref().map(|results_dir| results_dir.join("logs").join(test_name));
+        if let Some(log_dir) = &log_dir {
+            write_logs(log_dir, test, evaluation.result(), evaluation.execution(), Some(elapsed))?;
+        }
+
+        // (6) Print result and store it
+        let usage = evaluation.execution().and_then(Execution::usage).copied();
+        let shown_usage = usage.as_ref().filter(|_| args.show_usage);
+
+        match evaluation.result() {
+            TestResult::Passed => print_result(test.file_name(), "PASS", None, Some(elapsed), shown_usage),
             TestResult::Failed(reason) => {
-                print_result(test.file_name(), "FAIL", Some(&reason.to_string()), Some(elapsed));
+                print_result(test.file_name(), "FAIL", Some(&reason.to_string()), Some(elapsed), shown_usage);
+                print_failure_logs(log_dir.as_deref(), evaluation.execution(), args.stderr_tail);
             }
             TestResult::Skipped(reason) => {
-                print_result(test.file_name(), "SKIP", Some(&reason.to_string()), Some(elapsed));
+                print_result(test.file_name(), "SKIP", Some(&reason.to_string()), Some(elapsed), shown_usage);
             }
         }
 
-        results.push((test.file_name().to_string(), result));
+        if let Some(usage) = usage {
+            usages.push((test.file_name().to_string(), usage));
+        }
+
+        // (7) Keep or remove the working directory
+        let keep = match args.keep_workdirs {
+            Some(KeepWorkdirs::All) => true,
+            Some(KeepWorkdirs::Failed) => evaluation.result().is_failed(),
+            None => false,
+        };
+
+        if keep {
+            kept_workdirs += 1;
+        } else {
+            std::fs::remove_dir_all(&workdir)
+                .with_context(|| format!("removing working directory `{}`", workdir.display()))?;
+        }
+
+        records.push(Record::new(
+            test,
+            evaluation.result().clone(),
+            Some(elapsed),
+            evaluation.execution(),
+        ));
+    }
+
+    if kept_workdirs > 0 {
+        println!("\nKept {} working {} in `{}`", kept_workdirs, if kept_workdirs == 1 { "directory" } else { "directories" }, workdirs_dir.display());
     }
 
     //===================//
@@ -280,14 +582,14 @@ pub fn main(mut args: Args) -> Result<()> {
     println!("{}", "=".repeat(60));
     println!();
 
-    let passed = results.iter().filter(|(_, r)| r.is_passed()).count();
-    let failed = results.iter().filter(|(_, r)| r.is_failed()).count();
-    let skipped = results.iter().filter(|(_, r)| r.is_skipped()).count();
+    let suite = Suite::new(name, commit, source_url, records);
+    let passed = suite.passed();
+    let failed = suite.failed();
 
     println!("Passed:  {}", passed);
     println!("Failed:  {}", failed);
-    println!("Skipped: {}", skipped);
-    println!("Total:   {}", results.len());
+    println!("Skipped: {}", suite.skipped());
+    println!("Total:   {}", suite.records().len());
     println!();
     println!("Total time:   {:.2}s", total_elapsed.as_secs_f64());
 
@@ -297,150 +599,156 @@ pub fn main(mut args: Args) -> Result<()> {
         println!("Average time: {:.2}s per test", avg_time);
     }
 
-    if failed > 0 {
-        bail!("{} test(s) failed", failed);
+    if args.show_usage && !usages.is_empty() {
+        print_heaviest_tests(&mut usages);
     }
 
-    Ok(())
+    Ok(Summary { suite, kept_workdirs })
 }
 
-/// Creates an `input.json` file.
-fn create_input_json(test: &Test, work_dir: &Path) -> Result<PathBuf> {
-    let input = match test.input() {
-        Some(value) => serde_json::to_string_pretty(value)
-            .context("serializing input file")?,
-        None => Default::default(),
-    };
+/// Gets the name of the subdirectory for the suite of a branch.
+///
+/// Characters that are not allowed in file names on common platforms are
+/// replaced with `-` (e.g., `refs/pull/7/head` becomes `refs-pull-7-head`).
+fn suite_dir_name(branch: &str) -> String {
+    branch
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '-' })
+        .collect()
+}
 
-    let input_file_path = work_dir.join("inputs.json");
-    std::fs::write(&input_file_path, input)
-        .context("writing `inputs.json` file")?;
+/// Prints the summary of each suite when several suites are run.
+fn print_version_summary(summaries: &[Summary]) {
+    println!("\n{}", "=".repeat(60));
+    println!("Version Summary");
+    println!("{}", "=".repeat(60));
+    println!();
+
+    let width = summaries.iter().map(|summary| summary.suite.name().len()).max().unwrap_or_default();
 
-    Ok(input_file_path)
+    for Summary { suite, .. } in summaries {
+        let status = if suite.failed() > 0 { "\x1b[31mFAIL\x1b[0m" } else { "\x1b[32mPASS\x1b[0m" };
+
+        println!(
+            "{:width$}  {}  {} passed, {} failed, {} skipped",
+            suite.name(),
+            status,
+            suite.passed(),
+            suite.failed(),
+            suite.skipped()
+        );
+    }
 }
 
-/// Executes a test and evaluates the result.
-fn execute_and_evaluate_test(
+/// Writes the logs of a test to a directory.
+///
+/// The result of the test is always written to the execution log, even if
+/// the engine was never executed (e.g., because the inputs could not be
+/// prepared), so every failure leaves a log behind. The stdout and stderr
+/// logs are only written for an execution. Any logs from a previous run
+/// within the directory are replaced.
+fn write_logs(
+    log_dir: &Path,
     test: &Test,
-    command: &str,
-    root_dir: &Path,
-    workdir: &Path,
-    redirect_stdout: bool,
-    output_selector: Option<&str>,
-) -> TestResult {
-    // Execute the command
-    let output = match Command::new("bash")
-        .args(["-c", command])
-        .current_dir(root_dir)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-    {
-        Ok(output) => output,
-        Err(e) => {
-            return TestResult::Failed(FailureReason::ExecutionError(e.to_string()));
-        }
-    };
-
-    let exit_code = output.status.code().unwrap_or(-1);
+    result: &TestResult,
+    execution: Option<&Execution>,
+    elapsed: Option<Duration>,
+) -> Result<()> {
+    if log_dir.exists() {
+        std::fs::remove_dir_all(log_dir)
+            .with_context(|| format!("removing log directory `{}`", log_dir.display()))?;
+    }
 
-    tracing::trace!("stdout: {}", String::from_utf8_lossy(&output.stdout));
-    tracing::trace!("stderr: {}", String::from_utf8_lossy(&output.stderr));
+    std::fs::create_dir_all(log_dir)
+        .with_context(|| format!("creating log directory `{}`", log_dir.display()))?;
 
-    // Write stdout to `outputs.json` if `redirect_stdout` is enabled
-    if redirect_stdout {
-        let outputs_path = workdir.join("outputs.json");
-        if let Err(e) = std::fs::write(&outputs_path, &output.stdout) {
-            return TestResult::Failed(FailureReason::ExecutionError(format!(
-                "failed to write stdout to `outputs.json`: {}",
-                e
-            )));
-        }
+    if let Some(execution) = execution {
+        std::fs::write(log_dir.join(STDOUT_LOG_FILE_NAME), execution.stdout())
+            .context("writing stdout log")?;
+        std::fs::write(log_dir.join(STDERR_LOG_FILE_NAME), execution.stderr())
+            .context("writing stderr log")?;
     }
 
-    // Determine if test should have failed
-    let expected_to_fail = test.config().fail();
+    let (status, reason) = match result {
+        TestResult::Passed => ("pass", None),
+        TestResult::Failed(reason) => ("fail", Some(reason.to_string())),
+        TestResult::Skipped(reason) => ("skip", Some(reason.to_string())),
+    };
+
+    let usage = execution.and_then(Execution::usage);
+    let summary = serde_json::json!({
+        "origin": test.origin(),
+        "status": status,
+        "reason": reason,
+        "command": execution.map(Execution::command),
+        "exit_code": execution.map(Execution::exit_code),
+        "elapsed_secs": elapsed.map(|elapsed| elapsed.as_secs_f64()),
+        "user_time_secs": usage.map(|u| u.user_time().as_secs_f64()),
+        "system_time_secs": usage.map(|u| u.system_time().as_secs_f64()),
+        "max_rss_bytes": usage.map(ResourceUsage::max_rss),
+    });
+
+    std::fs::write(
+        log_dir.join(EXECUTION_LOG_FILE_NAME),
+        serde_json::to_string_pretty(&summary).context("serializing execution log")?,
+    )
+    .context("writing execution log")?;
+
+    Ok(())
+}
 
-    // If test is expected to fail, check if command failed (non-zero exit)
-    if expected_to_fail {
-        if exit_code == 0 {
-            return TestResult::Failed(FailureReason::UnexpectedSuccess);
+/// Prints the log paths and the trailing stderr lines of a failed test.
+fn print_failure_logs(log_dir: Option<&Path>, execution: Option<&Execution>, stderr_tail: usize) {
+    if let Some(log_dir) = log_dir {
+        if execution.is_some() {
+            println!("  stdout: {}", log_dir.join(STDOUT_LOG_FILE_NAME).display());
+            println!("  stderr: {}", log_dir.join(STDERR_LOG_FILE_NAME).display());
         } else {
-            return TestResult::Passed;
+            println!("  log: {}", log_dir.join(EXECUTION_LOG_FILE_NAME).display());
         }
     }
 
-    // Check return code
-    let return_code_matches = match test.config().return_code() {
-        ReturnCode::Any => true,
-        ReturnCode::Single(expected) => exit_code == *expected,
-        ReturnCode::Multiple(expected) => expected.contains(&exit_code),
+    let Some(execution) = execution else {
+        return;
     };
 
-    // If return code doesn't match, test failed
-    if !return_code_matches {
-        return TestResult::Failed(FailureReason::ReturnCodeMismatch {
-            expected: test.config().return_code().clone(),
-            actual: exit_code,
-        });
+    let stderr = String::from_utf8_lossy(execution.stderr());
+    let lines = stderr.lines().collect::<Vec<_>>();
+    for line in &lines[lines.len().saturating_sub(stderr_tail)..] {
+        println!("  | {}", line);
     }
+}
 
-    // If we have expected output, validate it
-    if let Some(expected_output) = test.output() {
-        let outputs_path = workdir.join("outputs.json");
-
-        let actual_output = match std::fs::read_to_string(&outputs_path) {
-            Ok(content) => content,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-                return TestResult::Failed(FailureReason::NoOutput);
-            }
-            Err(e) => {
-                return TestResult::Failed(FailureReason::OutputMismatch {
-                    details: format!("failed to read `outputs.json`: {}", e),
-                });
-            }
-        };
-
-        // Check if `outputs.json` is empty
-        if actual_output.trim().is_empty() {
-            return TestResult::Failed(FailureReason::NoOutput);
-        }
-
-        let actual_output: serde_json::Value = match serde_json::from_str(&actual_output) {
-            Ok(value) => value,
-            Err(e) => {
-                return TestResult::Failed(FailureReason::OutputMismatch {
-                    details: format!("failed to parse `outputs.json`: {}", e),
-                });
-            }
-        };
-
-        // Apply output selector if provided
-        let actual_output = if let Some(selector) = output_selector {
-            match apply_selector(selector, &actual_output) {
-                Ok(transformed) => transformed,
-                Err(failure_reason) => return TestResult::Failed(failure_reason),
-            }
-        } else {
-            actual_output
-        };
+/// Prints the tests that used the most CPU time and memory.
+fn print_heaviest_tests(usages: &mut [(String, ResourceUsage)]) {
+    /// The number of tests to list for each resource.
+    const COUNT: usize = 5;
 
-        if let Err(e) = validate_outputs(
-            expected_output,
-            &actual_output,
-            test.config().exclude_outputs(),
-        ) {
-            return TestResult::Failed(FailureReason::OutputMismatch {
-                details: e.to_string(),
-            });
-        }
+    println!();
+    println!("Most CPU time:");
+    usages.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.cpu_time()));
+    for (name, usage) in usages.iter().take(COUNT) {
+        println!("  {:.2}s  {}", usage.cpu_time().as_secs_f64(), name);
     }
 
-    TestResult::Passed
+    println!();
+    println!("Most memory:");
+    usages.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.max_rss()));
+    for (name, usage) in usages.iter().take(COUNT) {
+        println!("  {:.1} MiB  {}", usage.max_rss() as f64 / (1024.0 * 1024.0), name);
+    }
 }
 
 /// Prints a test result in the format: <test_name>...RESULT [time]
-fn print_result(test_name: &str, status: &str, details: Option<&str>, elapsed: Option<std::time::Duration>) {
+///
+/// The resource usage is shown alongside the time, if provided.
+pub(crate) fn print_result(
+    test_name: &str,
+    status: &str,
+    details: Option<&str>,
+    elapsed: Option<std::time::Duration>,
+    usage: Option<&ResourceUsage>,
+) {
     const TOTAL_WIDTH: usize = 50;
 
     let dots_len = TOTAL_WIDTH.saturating_sub(test_name.len());
@@ -453,7 +761,11 @@ fn print_result(test_name: &str, status: &str, details: Option<&str>, elapsed: O
         _ => ("", ""),
     };
 
-    let time_str = elapsed.map(|d| format!(" [{:.2}s]", d.as_secs_f64())).unwrap_or_default();
+    let time_str = match (elapsed, usage) {
+        (Some(d), Some(usage)) => format!(" [{:.2}s; {}]", d.as_secs_f64(), usage),
+        (Some(d), None) => format!(" [{:.2}s]", d.as_secs_f64()),
+        (None, _) => String::new(),
+    };
 
     if let Some(details_str) = details {
         println!("{}{}{}{}{}{} ({})", test_name, dots, color_code, status, reset_code, time_str, details_str);
@@ -462,74 +774,28 @@ fn print_result(test_name: &str, status: &str, details: Option<&str>, elapsed: O
     }
 }
 
-/// Applies a `jq` selector to a JSON value.
-fn apply_selector(selector: &str, input: &serde_json::Value) -> Result<serde_json::Value, FailureReason> {
-    use jaq_core::{data, unwrap_valr, Compiler, Ctx, Vars};
-    use jaq_core::load::{Arena, File, Loader};
-    use jaq_json::Val;
-
-    let program = File { code: selector, path: () };
-    let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
-    let arena = Arena::default();
-
-    // Parse the selector
-    let modules = loader.load(&arena, program).map_err(|errs| {
-        let error_msg = errs.into_iter().map(|(file, err)| format!("{}: {:?}", file.code, err)).collect::<Vec<_>>().join("; ");
-        FailureReason::SelectorError {
-            selector: selector.to_string(),
-            details: error_msg,
-        }
-    })?;
-
-    // Compile the selector
-    let filter = Compiler::default()
-        .with_funs(jaq_std::funs().chain(jaq_json::funs()))
-        .compile(modules)
-        .map_err(|errs| {
-            let error_msg = errs.into_iter().map(|(file, err)| {
-                let err_str = err.into_iter().map(|(name, _)| name).collect::<Vec<_>>().join(", ");
-                format!("{}: undefined: {}", file.code, err_str)
-            }).collect::<Vec<_>>().join("; ");
-            FailureReason::SelectorError {
-                selector: selector.to_string(),
-                details: error_msg,
-            }
-        })?;
-
-    // Convert `serde_json::Value` to `jaq` `Val` using JSON string roundtrip
-    let json_str = input.to_string();
-    let jaq_input = jaq_json::read::parse_single(json_str.as_bytes()).map_err(|e| FailureReason::SelectorError {
-        selector: selector.to_string(),
-        details: format!("failed to parse input as JSON: {}", e),
-    })?;
-
-    // Execute the selector
-    let ctx = Ctx::<data::JustLut<Val>>::new(&filter.lut, Vars::new([]));
-    let mut outputs = filter.id.run((ctx, jaq_input)).map(unwrap_valr);
-
-    // Expect exactly one output
-    let first_output = outputs.next();
-    let second_output = outputs.next();
-
-    match (first_output, second_output) {
-        (None, _) => Err(FailureReason::SelectorError {
-            selector: selector.to_string(),
-            details: "selector produced no output".to_string(),
-        }),
-        (Some(Err(e)), _) => Err(FailureReason::SelectorError {
-            selector: selector.to_string(),
-            details: format!("selector execution failed: {}", e),
-        }),
-        (Some(Ok(_)), Some(_)) => Err(FailureReason::SelectorError {
-            selector: selector.to_string(),
-            details: "selector produced multiple outputs (expected exactly one)".to_string(),
-        }),
-        (Some(Ok(val)), None) => {
-            let json_str = val.to_string();
-            serde_json::from_str(&json_str).map_err(|e| FailureReason::SelectorError {
-                selector: selector.to_string(),
-                details: format!("failed to convert result to JSON: {}", e),
-            })
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::test::example;
+    use crate::conformance::FailureReason;
+    use crate::conformance::Tests;
+
+    #[test]
+    fn writes_logs_without_execution() {
+        let tests = Tests::compile(example("hello.wdl", "version 1.2\nworkflow hello {}", None, None)).unwrap();
+        let test = tests.tests().next().unwrap();
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let log_dir = tempdir.path().join("hello");
+        let result = TestResult::Failed(FailureReason::InputError(String::from("invalid input selector")));
+        write_logs(&log_dir, test, &result, None, None).unwrap();
+
+        assert!(!log_dir.join(STDOUT_LOG_FILE_NAME).exists());
+        let log: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(log_dir.join(EXECUTION_LOG_FILE_NAME)).unwrap()).unwrap();
+        assert_eq!(log["status"], "fail");
+        assert_eq!(log["reason"], "input error: invalid input selector");
+        assert!(log["exit_code"].is_null());
     }
 }
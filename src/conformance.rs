@@ -1,12 +1,27 @@
+pub mod diff;
+mod document;
+pub mod engine;
+pub mod probe;
 mod resource;
 pub mod test;
 
+pub use document::Document;
+pub use engine::CommandEngine;
+pub use engine::Engine;
+pub use engine::Execution;
+pub use engine::Limit;
+pub use engine::ResourceLimits;
+pub use engine::ResourceUsage;
+
 pub use resource::Resource;
 pub use resource::Resources;
 pub use test::Capability;
+pub use test::FailPhase;
 pub use test::FailureReason;
+pub use test::KeyStyle;
 pub use test::ReturnCode;
 pub use test::SkipReason;
+pub use test::Stream;
 pub use test::Target;
 pub use test::Test;
 pub use test::TestResult;
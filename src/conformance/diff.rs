@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::conformance::Test;
+use crate::conformance::Tests;
+
+/// A part of a conformance test that changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aspect {
+    /// The WDL source.
+    Source,
+
+    /// The example input.
+    Input,
+
+    /// The example output.
+    Output,
+
+    /// The test configuration.
+    Config,
+}
+
+impl std::fmt::Display for Aspect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Aspect::Source => write!(f, "source"),
+            Aspect::Input => write!(f, "input"),
+            Aspect::Output => write!(f, "output"),
+            Aspect::Config => write!(f, "config"),
+        }
+    }
+}
+
+/// A conformance test that changed between two sets of tests.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Change {
+    /// The name of the test.
+    name: String,
+
+    /// The parts of the test that changed.
+    aspects: Vec<Aspect>,
+}
+
+impl Change {
+    /// Gets the name of the test.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the parts of the test that changed.
+    pub fn aspects(&self) -> &[Aspect] {
+        &self.aspects
+    }
+}
+
+/// The differences between two sets of conformance tests.
+///
+/// Tests are matched by name, and each list is sorted by name.
+#[derive(Debug, Default, Serialize)]
+pub struct Diff {
+    /// The names of the tests that were added.
+    added: Vec<String>,
+
+    /// The names of the tests that were removed.
+    removed: Vec<String>,
+
+    /// The tests that changed.
+    changed: Vec<Change>,
+
+    /// The number of tests that are unchanged.
+    unchanged: usize,
+}
+
+impl Diff {
+    /// Compares an old set of conformance tests with a new one.
+    pub fn new(old: &Tests, new: &Tests) -> Self {
+        let old = by_name(old);
+        let new = by_name(new);
+        let mut diff = Self::default();
+
+        for (name, old_test) in &old {
+            match new.get(name) {
+                Some(new_test) => {
+                    let aspects = compare(old_test, new_test);
+
+                    if aspects.is_empty() {
+                        diff.unchanged += 1;
+                    } else {
+                        diff.changed.push(Change {
+                            name: name.to_string(),
+                            aspects,
+                        });
+                    }
+                }
+                None => diff.removed.push(name.to_string()),
+            }
+        }
+
+        diff.added = new
+            .keys()
+            .filter(|name| !old.contains_key(*name))
+            .map(|name| name.to_string())
+            .collect();
+
+        diff
+    }
+
+    /// Gets the names of the tests that were added.
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+
+    /// Gets the names of the tests that were removed.
+    pub fn removed(&self) -> &[String] {
+        &self.removed
+    }
+
+    /// Gets the tests that changed.
+    pub fn changed(&self) -> &[Change] {
+        &self.changed
+    }
+
+    /// Gets the number of tests that are unchanged.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    /// Returns whether the sets of tests are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Indexes a set of conformance tests by name.
+fn by_name(tests: &Tests) -> BTreeMap<&str, &Test> {
+    tests.tests().map(|test| (test.file_name(), test)).collect()
+}
+
+/// Compares two versions of a conformance test.
+fn compare(old: &Test, new: &Test) -> Vec<Aspect> {
+    let mut aspects = Vec::new();
+
+    if old.src().trim() != new.src().trim() {
+        aspects.push(Aspect::Source);
+    }
+
+    if old.input() != new.input() {
+        aspects.push(Aspect::Input);
+    }
+
+    if old.output() != new.output() {
+        aspects.push(Aspect::Output);
+    }
+
+    // NOTE: configurations are compared by their serialized form, which
+    // includes the defaults of any keys that are not set.
+    if serde_json::to_value(old.config()).ok() != serde_json::to_value(new.config()).ok() {
+        aspects.push(Aspect::Config);
+    }
+
+    aspects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::test::example;
+
+    #[test]
+    fn diff() {
+        let old = Tests::compile(
+            [
+                example("same.wdl", "version 1.1", Some("{}"), Some("{}")),
+                example("removed.wdl", "version 1.1", Some("{}"), Some("{}")),
+                example("source.wdl", "version 1.1", Some("{}"), Some("{}")),
+                example("output.wdl", "version 1.1", Some(r#"{"a": 1}"#), Some(r#"{"fail": true}"#)),
+            ]
+            .concat(),
+        )
+        .unwrap();
+
+        let new = Tests::compile(
+            [
+                example("same.wdl", "version 1.1", Some("{}"), Some(r#"{"fail": false}"#)),
+                example("source.wdl", "version 1.2", Some("{}"), Some("{}")),
+                example("output.wdl", "version 1.1", Some(r#"{"a": 2}"#), Some("{}")),
+                example("added.wdl", "version 1.2", Some("{}"), Some("{}")),
+            ]
+            .concat(),
+        )
+        .unwrap();
+
+        let diff = Diff::new(&old, &new);
+        assert_eq!(diff.added(), ["added.wdl"]);
+        assert_eq!(diff.removed(), ["removed.wdl"]);
+        assert_eq!(diff.unchanged(), 1);
+        assert_eq!(
+            diff.changed(),
+            [
+                Change {
+                    name: String::from("output.wdl"),
+                    aspects: vec![Aspect::Output, Aspect::Config],
+                },
+                Change {
+                    name: String::from("source.wdl"),
+                    aspects: vec![Aspect::Source],
+                },
+            ]
+        );
+        assert!(!diff.is_empty());
+        assert!(Diff::new(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use std::path::Component;
+use std::path::Path;
+
+/// A markdown document containing conformance tests and resources.
+#[derive(Debug)]
+pub struct Document {
+    /// The name of the document (e.g., the path it was read from).
+    name: String,
+
+    /// The contents of the document.
+    contents: String,
+}
+
+impl Document {
+    /// Creates a new document.
+    pub fn new(name: impl Into<String>, contents: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            contents: contents.into(),
+        }
+    }
+
+    /// Gets the name of the document.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the contents of the document.
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+}
+
+/// Gets the namespace of the tests within each document.
+///
+/// A namespace is the file stem of the name of a document (e.g., `SPEC` for
+/// `wdl/SPEC.md`). When several documents share a stem, the components of
+/// their parent paths are prepended until the namespaces are unique (e.g.,
+/// `a-SPEC` and `b-SPEC` for `a/SPEC.md` and `b/SPEC.md`), and documents that
+/// still cannot be told apart are suffixed with their position among them
+/// (e.g., `SPEC-2`).
+pub(crate) fn namespaces(documents: &[Document]) -> Vec<String> {
+    // The components of each name from the file stem to the root
+    let components = documents
+        .iter()
+        .map(|document| {
+            let path = Path::new(document.name());
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(document.name());
+
+            let parents = path
+                .parent()
+                .into_iter()
+                .flat_map(Path::components)
+                .filter_map(|component| match component {
+                    Component::Normal(name) => name.to_str(),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            std::iter::once(stem).chain(parents.into_iter().rev()).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let namespace = |components: &[&str], depth: usize| {
+        let mut components = components.iter().take(depth).copied().collect::<Vec<_>>();
+        components.reverse();
+        components.join("-")
+    };
+
+    let mut namespaces = Vec::with_capacity(documents.len());
+
+    for (i, own) in components.iter().enumerate() {
+        let others = components
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, components)| components)
+            .collect::<Vec<_>>();
+
+        let unique = (1..=own.len()).find(|&depth| {
+            let candidate = namespace(own, depth);
+            others.iter().all(|other| namespace(other, depth) != candidate)
+        });
+
+        match unique {
+            Some(depth) => namespaces.push(namespace(own, depth)),
+            None => {
+                let candidate = namespace(own, own.len());
+                let position = components[..i]
+                    .iter()
+                    .filter(|other| namespace(other, other.len()) == candidate)
+                    .count();
+
+                if position == 0 {
+                    namespaces.push(candidate);
+                } else {
+                    namespaces.push(format!("{candidate}-{}", position + 1));
+                }
+            }
+        }
+    }
+
+    namespaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_namespaces() {
+        let documents = [
+            Document::new("wdl/SPEC.md", ""),
+            Document::new("a/docs/SPEC.md", ""),
+            Document::new("b/docs/SPEC.md", ""),
+            Document::new("extensions.md", ""),
+            Document::new("stdin", ""),
+            Document::new("stdin", ""),
+        ];
+
+        assert_eq!(
+            namespaces(&documents),
+            ["wdl-SPEC", "a-docs-SPEC", "b-docs-SPEC", "extensions", "stdin", "stdin-2"]
+        );
+    }
+}
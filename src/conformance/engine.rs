@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use bon::Builder;
+use serde_json::Value;
+
+use crate::conformance::FailureReason;
+use crate::conformance::Test;
+
+mod command;
+pub mod limits;
+mod usage;
+
+pub use command::CommandEngine;
+pub use limits::Limit;
+pub use limits::ResourceLimits;
+pub use usage::ResourceUsage;
+
+/// A WDL execution engine that conformance tests are run against.
+///
+/// Engines that can be called in-process may implement this trait directly
+/// to run the conformance tests from their own test suites. The
+/// [`CommandEngine`] implementation calls an engine through a shell command
+/// template.
+pub trait Engine {
+    /// Executes a conformance test.
+    ///
+    /// The inputs for the test have already been written to `input`, and the
+    /// resources for the test have been copied into `workdir`.
+    fn execute(&self, test: &Test, input: &Path, workdir: &Path) -> Result<Execution, FailureReason>;
+
+    /// Validates the document of a conformance test without executing it.
+    ///
+    /// Returns `None` if the engine does not support validating documents.
+    fn check(&self, _test: &Test, _input: &Path, _workdir: &Path) -> Option<Result<Execution, FailureReason>> {
+        None
+    }
+}
+
+/// The result of an engine executing (or checking) a conformance test.
+#[derive(Builder, Debug, Clone)]
+#[builder(builder_type = Builder)]
+pub struct Execution {
+    /// The command that was executed, if the engine was called through one.
+    #[builder(into)]
+    command: Option<String>,
+
+    /// The exit code.
+    ///
+    /// This is `-1` if the execution was terminated by a signal.
+    exit_code: i32,
+
+    /// The signal that terminated the execution, if any.
+    signal: Option<i32>,
+
+    /// Whether a core dump was produced when terminated by a signal.
+    #[builder(default)]
+    core_dumped: bool,
+
+    /// The outputs, if any were produced.
+    outputs: Option<Value>,
+
+    /// The resources used by the execution, if they were measured.
+    usage: Option<ResourceUsage>,
+
+    /// The resource limit that caused the execution to fail, if any.
+    exceeded_limit: Option<Limit>,
+
+    /// The captured stdout.
+    #[builder(default)]
+    stdout: Vec<u8>,
+
+    /// The captured stderr.
+    #[builder(default)]
+    stderr: Vec<u8>,
+}
+
+impl Execution {
+    /// Gets the command that was executed.
+    pub fn command(&self) -> Option<&str> {
+        self.command.as_deref()
+    }
+
+    /// Gets the exit code.
+    pub fn exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    /// Gets the signal that terminated the execution.
+    pub fn signal(&self) -> Option<i32> {
+        self.signal
+    }
+
+    /// Returns whether a core dump was produced when terminated by a signal.
+    pub fn core_dumped(&self) -> bool {
+        self.core_dumped
+    }
+
+    /// Gets the outputs.
+    pub fn outputs(&self) -> Option<&Value> {
+        self.outputs.as_ref()
+    }
+
+    /// Gets the resources used by the execution.
+    pub fn usage(&self) -> Option<&ResourceUsage> {
+        self.usage.as_ref()
+    }
+
+    /// Gets the resource limit that caused the execution to fail.
+    pub fn exceeded_limit(&self) -> Option<Limit> {
+        self.exceeded_limit
+    }
+
+    /// Gets the captured stdout.
+    pub fn stdout(&self) -> &[u8] {
+        &self.stdout
+    }
+
+    /// Gets the captured stderr.
+    pub fn stderr(&self) -> &[u8] {
+        &self.stderr
+    }
+}
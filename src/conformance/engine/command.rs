@@ -0,0 +1,325 @@
+use std::path::Path;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Output;
+
+use anyhow::Context;
+use anyhow::Result;
+use bon::Builder;
+use serde_json::Value;
+
+use crate::conformance::Engine;
+use crate::conformance::Execution;
+use crate::conformance::FailureReason;
+use crate::conformance::ResourceLimits;
+use crate::conformance::ResourceUsage;
+use crate::conformance::Test;
+use crate::shell;
+use crate::wdl;
+
+/// An engine that is called through a shell command template.
+///
+/// The placeholders in the templates (e.g., `~{path}`, `~{input}`,
+/// `~{output}`, and `~{target}`) are substituted for each test. Commands are
+/// run from the directory containing the WDL files of the tests.
+#[derive(Builder, Debug)]
+#[builder(builder_type = Builder)]
+pub struct CommandEngine {
+    /// The command template to call for each execution.
+    #[builder(into)]
+    command: String,
+
+    /// The command template to call to validate a document.
+    #[builder(into)]
+    check_command: Option<String>,
+
+    /// Arguments to append when running a workflow.
+    #[builder(into, default)]
+    workflow_target_args: String,
+
+    /// Arguments to append when running a task.
+    #[builder(into, default)]
+    task_target_args: String,
+
+    /// Whether stdout is written to the outputs file.
+    #[builder(default)]
+    redirect_stdout: bool,
+
+    /// Whether commands are split into arguments and executed directly
+    /// rather than through `bash`.
+    #[builder(default)]
+    exec: bool,
+
+    /// The resource limits applied to each process unless overridden by a
+    /// test.
+    #[builder(default)]
+    limits: ResourceLimits,
+}
+
+impl CommandEngine {
+    /// Validates that the command templates only use supported placeholders.
+    pub fn validate(&self) -> Result<()> {
+        let templates = [
+            ("command", Some(self.command.as_str())),
+            ("check command", self.check_command.as_deref()),
+            ("workflow target arguments", Some(self.workflow_target_args.as_str())),
+            ("task target arguments", Some(self.task_target_args.as_str())),
+        ];
+
+        for (name, template) in templates {
+            if let Some(template) = template {
+                shell::validate(template, shell::process_env).with_context(|| format!("validating the {name}"))?;
+
+                if self.exec && !template.trim().is_empty() {
+                    shell::split(template).with_context(|| format!("splitting the {name}"))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Substitutes a command template for a test.
+    fn substitute(
+        &self,
+        command: &str,
+        test: &Test,
+        input: &Path,
+        workdir: &Path,
+        workflow_target_args: &str,
+        task_target_args: &str,
+    ) -> Result<shell::Command, FailureReason> {
+        let path = test.path().expect("test to be written");
+
+        // The written test may have had a different version injected
+        let src = std::fs::read_to_string(path).unwrap_or_else(|_| test.src().to_string());
+
+        shell::substitute()
+            .command(command.to_string())
+            .path(path.to_path_buf())
+            .input(input.to_path_buf())
+            .output(workdir.join("outputs.json"))
+            .target(test.target().expect("target should be inferred").clone())
+            .workflow_target_args(workflow_target_args.to_string())
+            .task_target_args(task_target_args.to_string())
+            .workdir(workdir.to_path_buf())
+            .root_dir(root_dir(test).to_path_buf())
+            .test_name(test.file_name().trim_end_matches(".wdl").to_string())
+            .maybe_wdl_version(wdl::parse_version(&src).map(ToOwned::to_owned))
+            .exec(self.exec)
+            .call()
+            .map_err(|e| FailureReason::ExecutionError(e.to_string()))
+    }
+
+    /// Runs a command and captures its output and resource usage.
+    ///
+    /// Shell commands are run through `bash`, and the programs of commands
+    /// split into arguments are spawned directly.
+    fn run(
+        &self,
+        command: &shell::Command,
+        root_dir: &Path,
+        limits: ResourceLimits,
+    ) -> Result<(Output, Option<ResourceUsage>), FailureReason> {
+        let mut process = match command {
+            shell::Command::Exec(args) => {
+                let mut process = Command::new(&args[0]);
+                process.args(&args[1..]);
+                process
+            }
+            shell::Command::Shell(command) => {
+                let mut process = Command::new("bash");
+                process.args(["-c", command]);
+                process
+            }
+        };
+
+        super::limits::apply(&mut process, limits);
+
+        super::usage::output(process.current_dir(root_dir)).map_err(|e| FailureReason::ExecutionError(e.to_string()))
+    }
+}
+
+impl Engine for CommandEngine {
+    fn execute(&self, test: &Test, input: &Path, workdir: &Path) -> Result<Execution, FailureReason> {
+        let command = self.substitute(
+            &self.command,
+            test,
+            input,
+            workdir,
+            &self.workflow_target_args,
+            &self.task_target_args,
+        )?;
+
+        tracing::debug!("executing command `{}`", command);
+
+        let limits = test.config().limits().or(self.limits);
+        let (output, usage) = self.run(&command, root_dir(test), limits)?;
+        let exceeded_limit = super::limits::exceeded(&limits, &output, usage.as_ref());
+        let exit_code = output.status.code().unwrap_or(-1);
+        let (signal, core_dumped) = termination_signal(&output.status);
+
+        tracing::trace!("stdout: {}", String::from_utf8_lossy(&output.stdout));
+        tracing::trace!("stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+        // Write stdout to `outputs.json` if `redirect_stdout` is enabled
+        let outputs_path = workdir.join("outputs.json");
+        if self.redirect_stdout {
+            if let Err(e) = std::fs::write(&outputs_path, &output.stdout) {
+                return Err(FailureReason::ExecutionError(format!(
+                    "failed to write stdout to `outputs.json`: {}",
+                    e
+                )));
+            }
+        }
+
+        // Only tests with an expected output are evaluated on `outputs.json`
+        let outputs = match test.output() {
+            Some(_) => match read_outputs(&outputs_path) {
+                Ok(outputs) => outputs,
+                // Failed executions may leave behind outputs that are not
+                // valid JSON, and tests expected to fail are not evaluated on
+                // outputs
+                Err(_) if !output.status.success() || test.config().fail() => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+
+        Ok(Execution::builder()
+            .command(command.to_string())
+            .exit_code(exit_code)
+            .maybe_signal(signal)
+            .core_dumped(core_dumped)
+            .maybe_outputs(outputs)
+            .maybe_usage(usage)
+            .maybe_exceeded_limit(exceeded_limit)
+            .stdout(output.stdout)
+            .stderr(output.stderr)
+            .build())
+    }
+
+    fn check(&self, test: &Test, input: &Path, workdir: &Path) -> Option<Result<Execution, FailureReason>> {
+        let check_command = self.check_command.as_deref()?;
+        let command = match self.substitute(check_command, test, input, workdir, "", "") {
+            Ok(command) => command,
+            Err(reason) => return Some(Err(reason)),
+        };
+
+        tracing::debug!("executing check command `{}`", command);
+
+        let limits = test.config().limits().or(self.limits);
+        Some(self.run(&command, root_dir(test), limits).map(|(output, usage)| {
+            let (signal, core_dumped) = termination_signal(&output.status);
+            let exceeded_limit = super::limits::exceeded(&limits, &output, usage.as_ref());
+
+            Execution::builder()
+                .command(command.to_string())
+                .exit_code(output.status.code().unwrap_or(-1))
+                .maybe_signal(signal)
+                .core_dumped(core_dumped)
+                .maybe_usage(usage)
+                .maybe_exceeded_limit(exceeded_limit)
+                .stdout(output.stdout)
+                .stderr(output.stderr)
+                .build()
+        }))
+    }
+}
+
+/// Gets the directory containing the written WDL file of a test.
+fn root_dir(test: &Test) -> &Path {
+    test.path().and_then(Path::parent).unwrap_or(Path::new(""))
+}
+
+/// Gets the signal that terminated a process and whether a core dump was
+/// produced.
+#[cfg(unix)]
+fn termination_signal(status: &ExitStatus) -> (Option<i32>, bool) {
+    use std::os::unix::process::ExitStatusExt;
+
+    (status.signal(), status.core_dumped())
+}
+
+/// Gets the signal that terminated a process and whether a core dump was
+/// produced.
+///
+/// Processes are not terminated by signals on this platform.
+#[cfg(not(unix))]
+fn termination_signal(_: &ExitStatus) -> (Option<i32>, bool) {
+    (None, false)
+}
+
+/// Reads the `outputs.json` file.
+///
+/// Returns `None` if the file does not exist or is empty.
+fn read_outputs(path: &Path) -> Result<Option<Value>, FailureReason> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(FailureReason::OutputMismatch {
+                details: format!("failed to read `outputs.json`: {}", e),
+            });
+        }
+    };
+
+    // Check if `outputs.json` is empty
+    if contents.trim().is_empty() {
+        return Ok(None);
+    }
+
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| FailureReason::OutputMismatch {
+            details: format!("failed to parse `outputs.json`: {}", e),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::conformance::test::Config;
+
+    fn test(dir: &Path, output: Option<Value>) -> Test {
+        let mut test = Test::builder()
+            .file_name("hello.wdl".to_string())
+            .src("version 1.2\nworkflow hello {}".to_string())
+            .maybe_output(output)
+            .config(Config::default())
+            .build();
+        test.infer_and_validate_target().unwrap();
+        test.set_path(dir.join("hello.wdl"));
+        test
+    }
+
+    #[test]
+    fn reads_outputs_only_when_expected() {
+        let workdir = tempfile::tempdir().unwrap();
+        let engine = CommandEngine::builder()
+            .command("echo not-json")
+            .redirect_stdout(true)
+            .build();
+
+        let execution = engine.execute(&test(workdir.path(), None), Path::new("inputs.json"), workdir.path()).unwrap();
+        assert_eq!(execution.outputs(), None);
+        assert!(!execution.stdout().is_empty());
+
+        let result = engine.execute(&test(workdir.path(), Some(json!({"hello.out": "hi"}))), Path::new("inputs.json"), workdir.path());
+        assert!(matches!(result, Err(FailureReason::OutputMismatch { .. })));
+    }
+    #[test]
+    fn runs_from_root_dir() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let workdir = tempfile::tempdir().unwrap();
+        let engine = CommandEngine::builder().command("pwd").build();
+
+        let execution = engine.execute(&test(root_dir.path(), None), Path::new("inputs.json"), workdir.path()).unwrap();
+        let cwd = PathBuf::from(String::from_utf8_lossy(execution.stdout()).trim());
+        assert_eq!(cwd.canonicalize().unwrap(), root_dir.path().canonicalize().unwrap());
+    }
+}
@@ -0,0 +1,306 @@
+use std::process::Command;
+use std::process::Output;
+use std::sync::LazyLock;
+
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bon::Builder;
+use regex::Regex;
+
+use crate::conformance::ResourceUsage;
+
+/// The regex for stderr output indicating that memory could not be allocated.
+static OUT_OF_MEMORY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)out of memory|cannot allocate memory|memory allocation of \d+ bytes failed|MemoryError|OutOfMemoryError|bad_alloc",
+    )
+    .unwrap()
+});
+
+/// The regex for stderr output indicating that a file could not be opened
+/// because too many are open.
+static TOO_MANY_OPEN_FILES_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)too many open files|os error 24").unwrap());
+
+/// The limits on the resources of an engine process.
+///
+/// The limits are applied to the spawned process and inherited by any
+/// processes it spawns, each of which is limited individually.
+#[derive(Builder, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[builder(builder_type = Builder)]
+pub struct ResourceLimits {
+    /// The maximum size of the virtual memory of a process in bytes.
+    memory: Option<u64>,
+
+    /// The maximum CPU time of a process in seconds.
+    cpu_time: Option<u64>,
+
+    /// The maximum number of open files of a process.
+    open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Gets the maximum size of the virtual memory in bytes.
+    pub fn memory(&self) -> Option<u64> {
+        self.memory
+    }
+
+    /// Gets the maximum CPU time in seconds.
+    pub fn cpu_time(&self) -> Option<u64> {
+        self.cpu_time
+    }
+
+    /// Gets the maximum number of open files.
+    pub fn open_files(&self) -> Option<u64> {
+        self.open_files
+    }
+
+    /// Returns whether no limits are set.
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_none() && self.cpu_time.is_none() && self.open_files.is_none()
+    }
+
+    /// Returns the limits in `self`, falling back to those in `other` for any
+    /// limit that is not set.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            memory: self.memory.or(other.memory),
+            cpu_time: self.cpu_time.or(other.cpu_time),
+            open_files: self.open_files.or(other.open_files),
+        }
+    }
+}
+
+/// A resource limit that was exceeded by an engine process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// The memory limit in bytes.
+    Memory(u64),
+    /// The CPU time limit in seconds.
+    CpuTime(u64),
+    /// The open files limit.
+    OpenFiles(u64),
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Memory(bytes) => write!(f, "memory limit of {:.1} MiB", *bytes as f64 / (1024.0 * 1024.0)),
+            Limit::CpuTime(secs) => write!(f, "CPU time limit of {}s", secs),
+            Limit::OpenFiles(count) => write!(f, "open files limit of {}", count),
+        }
+    }
+}
+
+/// Parses a memory size.
+///
+/// Sizes are a number of bytes with an optional binary suffix (`K`, `M`,
+/// `G`, or `T`), such as `512M` or `2GiB`.
+pub fn parse_memory(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, suffix) = s.split_at(digits);
+
+    let number = number
+        .parse::<u64>()
+        .with_context(|| format!("invalid memory size `{s}`"))?;
+
+    let shift = match suffix.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => bail!("invalid memory size `{s}` (expected a number of bytes with an optional `K`, `M`, `G`, or `T` suffix)"),
+    };
+
+    number
+        .checked_mul(1 << shift)
+        .with_context(|| format!("memory size `{s}` is too large"))
+}
+
+/// Applies resource limits to a command before it is spawned.
+#[cfg(unix)]
+pub(crate) fn apply(command: &mut Command, limits: ResourceLimits) {
+    use std::os::unix::process::CommandExt;
+
+    if limits.is_empty() {
+        return;
+    }
+
+    // SAFETY: the closure only calls `setrlimit`, which is async-signal-safe.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(memory) = limits.memory {
+                set(libc::RLIMIT_AS, memory, memory)?;
+            }
+
+            // The hard limit is one second past the soft limit so that the
+            // process receives `SIGXCPU` before it is killed
+            if let Some(cpu_time) = limits.cpu_time {
+                set(libc::RLIMIT_CPU, cpu_time, cpu_time.saturating_add(1))?;
+            }
+
+            if let Some(open_files) = limits.open_files {
+                set(libc::RLIMIT_NOFILE, open_files, open_files)?;
+            }
+
+            Ok(())
+        });
+    }
+}
+
+/// Applies resource limits to a command before it is spawned.
+///
+/// Resource limits are not supported on this platform.
+#[cfg(not(unix))]
+pub(crate) fn apply(_: &mut Command, limits: ResourceLimits) {
+    if !limits.is_empty() {
+        tracing::warn!("resource limits are not supported on this platform");
+    }
+}
+
+/// Sets a resource limit of the current process.
+#[cfg(unix)]
+fn set(resource: ResourceKind, soft: u64, hard: u64) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: soft as libc::rlim_t,
+        rlim_max: hard as libc::rlim_t,
+    };
+
+    // SAFETY: `limit` is a valid pointer for the duration of the call.
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// The type of a resource passed to `setrlimit`.
+#[cfg(all(unix, target_os = "linux", target_env = "gnu"))]
+type ResourceKind = libc::__rlimit_resource_t;
+
+/// The type of a resource passed to `setrlimit`.
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type ResourceKind = libc::c_int;
+
+/// Determines which limit, if any, caused a process to fail.
+///
+/// Processes that exceed the CPU time limit are terminated by `SIGXCPU` (or
+/// `SIGKILL` at the hard limit). The limit applies to each process, whereas
+/// the usage includes descendants, so a failure is only attributed to the
+/// limit by its signal and never by the usage alone. The other limits cause
+/// system calls to fail, so they are inferred from the error reported to
+/// stderr by a failed process. This is a heuristic: a failure whose stderr
+/// happens to match (e.g., an engine reporting a `MemoryError` of its own) is
+/// attributed to a limit as long as that limit is set.
+pub(crate) fn exceeded(limits: &ResourceLimits, output: &Output, usage: Option<&ResourceUsage>) -> Option<Limit> {
+    if output.status.success() {
+        return None;
+    }
+
+    if let Some(cpu_time) = limits.cpu_time {
+        let over_time = usage.is_some_and(|usage| usage.cpu_time().as_secs() >= cpu_time);
+        if is_cpu_time_signal(output, over_time) {
+            return Some(Limit::CpuTime(cpu_time));
+        }
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if let Some(memory) = limits.memory {
+        if OUT_OF_MEMORY_REGEX.is_match(&stderr) {
+            return Some(Limit::Memory(memory));
+        }
+    }
+
+    if let Some(open_files) = limits.open_files {
+        if TOO_MANY_OPEN_FILES_REGEX.is_match(&stderr) {
+            return Some(Limit::OpenFiles(open_files));
+        }
+    }
+
+    None
+}
+
+/// Returns whether a process was terminated for exceeding its CPU time.
+///
+/// A `SIGKILL` is only attributed to the hard limit if the CPU time reached
+/// the limit.
+#[cfg(unix)]
+fn is_cpu_time_signal(output: &Output, over_time: bool) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+
+    match output.status.signal() {
+        Some(libc::SIGXCPU) => true,
+        Some(libc::SIGKILL) => over_time,
+        _ => false,
+    }
+}
+
+/// Returns whether a process was terminated for exceeding its CPU time.
+#[cfg(not(unix))]
+fn is_cpu_time_signal(_: &Output, _: bool) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_memory() {
+        assert_eq!(parse_memory("1024").unwrap(), 1024);
+        assert_eq!(parse_memory("4K").unwrap(), 4096);
+        assert_eq!(parse_memory("512M").unwrap(), 512 << 20);
+        assert_eq!(parse_memory("2GiB").unwrap(), 2 << 30);
+        assert!(parse_memory("2X").is_err());
+        assert!(parse_memory("M").is_err());
+        assert!(parse_memory("99999999999T").is_err());
+    }
+
+    #[test]
+    fn falls_back_to_other_limits() {
+        let test = ResourceLimits::builder().memory(1).build();
+        let run = ResourceLimits::builder().memory(2).cpu_time(3).build();
+
+        let limits = test.or(run);
+        assert_eq!(limits.memory(), Some(1));
+        assert_eq!(limits.cpu_time(), Some(3));
+        assert_eq!(limits.open_files(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn detects_exceeded_limits() {
+        let mut command = Command::new("bash");
+        command.args(["-c", "exec 3</dev/null 4</dev/null 5</dev/null"]);
+
+        let limits = ResourceLimits::builder().open_files(4).build();
+        apply(&mut command, limits);
+
+        let output = command.output().unwrap();
+        assert_eq!(exceeded(&limits, &output, None), Some(Limit::OpenFiles(4)));
+
+        let output = Command::new("true").output().unwrap();
+        assert_eq!(exceeded(&limits, &output, None), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn attributes_cpu_time_to_signals() {
+        use std::time::Duration;
+
+        let limits = ResourceLimits::builder().cpu_time(1).build();
+        let run = |script: &str| Command::new("bash").args(["-c", script]).output().unwrap();
+        let usage = ResourceUsage::new(Duration::from_secs(2), Duration::ZERO, 0);
+
+        // The CPU time of a process that failed otherwise is disregarded.
+        assert_eq!(exceeded(&limits, &run("exit 1"), Some(&usage)), None);
+
+        assert_eq!(exceeded(&limits, &run("kill -XCPU $$"), None), Some(Limit::CpuTime(1)));
+        assert_eq!(exceeded(&limits, &run("kill -KILL $$"), Some(&usage)), Some(Limit::CpuTime(1)));
+        assert_eq!(exceeded(&limits, &run("kill -KILL $$"), None), None);
+    }
+}
@@ -0,0 +1,162 @@
+use std::io::Read;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// The resources used by an execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// The CPU time spent in user mode.
+    user_time: Duration,
+
+    /// The CPU time spent in kernel mode.
+    system_time: Duration,
+
+    /// The peak resident set size in bytes.
+    max_rss: u64,
+}
+
+impl ResourceUsage {
+    /// Creates a new resource usage.
+    pub fn new(user_time: Duration, system_time: Duration, max_rss: u64) -> Self {
+        Self {
+            user_time,
+            system_time,
+            max_rss,
+        }
+    }
+
+    /// Gets the CPU time spent in user mode.
+    pub fn user_time(&self) -> Duration {
+        self.user_time
+    }
+
+    /// Gets the CPU time spent in kernel mode.
+    pub fn system_time(&self) -> Duration {
+        self.system_time
+    }
+
+    /// Gets the total CPU time.
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+
+    /// Gets the peak resident set size in bytes.
+    pub fn max_rss(&self) -> u64 {
+        self.max_rss
+    }
+}
+
+impl std::fmt::Display for ResourceUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.2}s user, {:.2}s sys, {:.1} MiB",
+            self.user_time.as_secs_f64(),
+            self.system_time.as_secs_f64(),
+            self.max_rss as f64 / (1024.0 * 1024.0)
+        )
+    }
+}
+
+/// Runs a command to completion, capturing its output and resource usage.
+///
+/// The stdout and stderr of the command are always captured. The resource
+/// usage includes any descendants that the command waited for.
+#[cfg(unix)]
+pub(crate) fn output(command: &mut Command) -> std::io::Result<(Output, Option<ResourceUsage>)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut child = command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+    // Read both streams concurrently so neither pipe fills up and blocks
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+
+    let mut status = 0;
+    // SAFETY: `rusage` is a plain C struct for which all zeroes is valid.
+    let mut rusage = unsafe { std::mem::zeroed::<libc::rusage>() };
+
+    loop {
+        // SAFETY: the process has not yet been waited for, so the pid is
+        // still owned by this process, and both pointers are valid.
+        let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, 0, &mut rusage) };
+
+        if pid >= 0 {
+            break;
+        }
+
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    let output = Output {
+        status: std::process::ExitStatus::from_raw(status),
+        stdout: stdout.map(join).transpose()?.unwrap_or_default(),
+        stderr: stderr.map(join).transpose()?.unwrap_or_default(),
+    };
+
+    // NOTE: the peak resident set size is reported in bytes on macOS and in
+    // kilobytes everywhere else.
+    let max_rss = rusage.ru_maxrss.max(0) as u64;
+    let max_rss = if cfg!(target_os = "macos") { max_rss } else { max_rss * 1024 };
+
+    let usage = ResourceUsage::new(duration(rusage.ru_utime), duration(rusage.ru_stime), max_rss);
+
+    Ok((output, Some(usage)))
+}
+
+/// Runs a command to completion, capturing its output.
+///
+/// Resource usage is not available on this platform.
+#[cfg(not(unix))]
+pub(crate) fn output(command: &mut Command) -> std::io::Result<(Output, Option<ResourceUsage>)> {
+    command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map(|output| (output, None))
+}
+
+/// Reads a stream to the end on a separate thread.
+#[cfg(unix)]
+fn read_to_end<R: Read + Send + 'static>(mut reader: R) -> std::thread::JoinHandle<std::io::Result<Vec<u8>>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
+
+/// Joins a thread reading a stream.
+#[cfg(unix)]
+fn join(handle: std::thread::JoinHandle<std::io::Result<Vec<u8>>>) -> std::io::Result<Vec<u8>> {
+    // SAFETY: the reading thread does not panic.
+    handle.join().unwrap()
+}
+
+/// Converts a `timeval` into a duration.
+#[cfg(unix)]
+fn duration(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec.max(0) as u64) + Duration::from_micros(time.tv_usec.max(0) as u64)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_output_and_usage() {
+        let mut command = Command::new("bash");
+        command.args(["-c", "echo out; echo err >&2; exit 3"]);
+
+        let (output, usage) = output(&mut command).unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+        assert!(usage.unwrap().max_rss() > 0);
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::bail;
+use anyhow::Result;
+
+use crate::conformance::Capability;
+use crate::conformance::Test;
+use crate::conformance::Tests;
+
+/// The probes, written in the same format as the conformance tests within the
+/// specification.
+pub const PROBES: &str = include_str!("probes.md");
+
+/// The version of WDL the probes are written in.
+pub const VERSION: &str = "1.2";
+
+/// Compiles the probes.
+pub fn compile() -> Result<Tests> {
+    Tests::compile(PROBES)
+}
+
+/// Gets the capability a probe detects.
+///
+/// Returns `None` for the probe of the WDL version, which requires no
+/// capability.
+pub fn capability(probe: &Test) -> Result<Option<&Capability>> {
+    match probe.config().capabilities() {
+        [] => Ok(None),
+        [capability] => Ok(Some(capability)),
+        _ => bail!("probe `{}` must require at most one capability", probe.file_name()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_every_capability() {
+        let tests = compile().unwrap();
+        let probed = tests
+            .tests()
+            .map(|probe| capability(probe).unwrap().cloned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(probed[0], None);
+        assert_eq!(probed[1..], Capability::KNOWN.iter().cloned().map(Some).collect::<Vec<_>>());
+
+        for probe in tests.tests() {
+            assert_eq!(crate::wdl::parse_version(probe.src()), Some(VERSION));
+        }
+    }
+}
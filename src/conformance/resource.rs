@@ -1,12 +1,15 @@
 use std::sync::LazyLock;
 
 use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
 use anyhow::Result;
-use bon::builder;
 use bon::Builder;
 use regex::Captures;
 use regex::Regex;
 
+use crate::conformance::Document;
+
 /// The regex for resource files the specification.
 static RESOURCE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     const PATTERN: &str = concat!(
@@ -58,6 +61,42 @@ impl Resources {
             .map(build_resource)
             .collect::<Result<Self, _>>()
     }
+
+    /// Turns several markdown documents into a single set of resources.
+    ///
+    /// A resource that is repeated with the same contents in a later document
+    /// is only included once.
+    pub fn compile_documents(documents: &[Document]) -> Result<Self> {
+        let mut resources = Vec::<Resource>::new();
+        let mut origins = Vec::<&Document>::new();
+
+        for document in documents {
+            let compiled = Self::compile(document.contents())
+                .with_context(|| format!("compiling resources within `{}`", document.name()))?;
+
+            // NOTE: resources are referenced by their file names within the
+            // tests, so resources with the same name in different documents
+            // cannot be renamed and must be identical. Documents are compared
+            // by identity, as several documents may have the same name.
+            for resource in compiled.0 {
+                match resources.iter().position(|r| r.filename == resource.filename) {
+                    Some(index) if resources[index].src == resource.src => continue,
+                    Some(index) if !std::ptr::eq(origins[index], document) => bail!(
+                        "resource `{}` differs between `{}` and `{}`",
+                        resource.filename,
+                        origins[index].name(),
+                        document.name()
+                    ),
+                    _ => {
+                        resources.push(resource);
+                        origins.push(document);
+                    }
+                }
+            }
+        }
+
+        Ok(Self(resources))
+    }
 }
 
 impl Resources {
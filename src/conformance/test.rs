@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
@@ -7,28 +8,36 @@ use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
-use bon::builder;
 use bon::Builder;
 use regex::Captures;
 use regex::Regex;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
+use crate::conformance::document;
+use crate::conformance::Document;
 use crate::wdl;
 
 mod config;
+pub mod evaluation;
 pub mod result;
 pub mod runner;
+pub mod selector;
+pub mod style;
 pub mod validation;
 
 pub use config::Capability;
 pub use config::Config;
+pub use config::FailPhase;
+pub use config::Pattern;
 pub use config::ReturnCode;
 pub use config::Tag;
 pub use result::FailureReason;
 pub use result::SkipReason;
+pub use result::Stream;
 pub use result::TestResult;
 pub use runner::Runner;
+pub use style::KeyStyle;
 pub use wdl::Target;
 
 /// The regex for a WDL conformance test within the specification.
@@ -75,6 +84,15 @@ pub struct Test {
 
     /// The inferred or validated target workflow/task.
     inferred_target: Option<wdl::Target>,
+
+    /// The name of the document the test was compiled from, if known.
+    origin: Option<String>,
+
+    /// The heading of the section of the document containing the test.
+    section: Option<String>,
+
+    /// The line of the document on which the test starts.
+    line: Option<usize>,
 }
 
 impl Test {
@@ -108,6 +126,22 @@ impl Test {
         &self.config
     }
 
+    /// The name of the document the test was compiled from, if known.
+    pub fn origin(&self) -> Option<&str> {
+        self.origin.as_deref()
+    }
+
+    /// The heading of the section of the document containing the test, if
+    /// any.
+    pub fn section(&self) -> Option<&str> {
+        self.section.as_deref()
+    }
+
+    /// The line of the document on which the test starts (starting at 1).
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
     /// Sets the path for the test.
     pub fn set_path(&mut self, path: PathBuf) {
         self.path = Some(path);
@@ -256,7 +290,7 @@ impl Test {
             let prefix = prefixes.into_iter().next().unwrap();
 
             // Check if prefix matches workflow or task
-            if matches!(decls.workflow(), Some(wf) if wf == &prefix) {
+            if matches!(decls.workflow(), Some(wf) if wf == prefix) {
                 Ok(Some(wdl::Target::Workflow(prefix)))
             } else if decls.tasks().contains(&prefix) {
                 Ok(Some(wdl::Target::Task(prefix)))
@@ -285,11 +319,68 @@ impl Tests {
     /// Turns a markdown specification into a set of conformance tests.
     pub fn compile<S: AsRef<str>>(contents: S) -> Result<Self> {
         let contents = contents.as_ref();
+        let headings = headings(contents);
 
         let tests = CONFORMANCE_TEST_REGEX
             .captures_iter(contents)
-            .map(build_conformance_test)
-            .collect::<Result<Vec<Test>, _>>()?;
+            .map(|captures| {
+                // SAFETY: the entire match is always present.
+                let start = captures.get(0).unwrap().start();
+                let mut test = build_conformance_test(captures)?;
+
+                test.line = Some(contents[..start].matches('\n').count() + 1);
+                test.section = headings
+                    .iter()
+                    .take_while(|(offset, _)| *offset < start)
+                    .last()
+                    .map(|(_, heading)| heading.clone());
+
+                Ok(test)
+            })
+            .collect::<Result<Vec<Test>>>()?;
+
+        Ok(Self(tests))
+    }
+
+    /// Turns several markdown documents into a single set of conformance
+    /// tests.
+    ///
+    /// The origin of each test is set to the name of its document. Tests with
+    /// the same name in different documents are namespaced by the document
+    /// (e.g., `hello.wdl` within `extensions.md` becomes
+    /// `extensions-hello.wdl`), using the parent path of documents with the
+    /// same file stem (e.g., `a-SPEC-hello.wdl` for `a/SPEC.md`).
+    pub fn compile_documents(documents: &[Document]) -> Result<Self> {
+        let namespaces = document::namespaces(documents);
+        let mut tests = Vec::new();
+
+        for (index, document) in documents.iter().enumerate() {
+            let compiled = Self::compile(document.contents())
+                .with_context(|| format!("compiling tests within `{}`", document.name()))?;
+
+            tests.extend(compiled.0.into_iter().map(|mut test| {
+                test.origin = Some(document.name().to_string());
+                (index, test)
+            }));
+        }
+
+        // NOTE: documents are told apart by their position rather than their
+        // name, as several documents may have the same name.
+        let mut origins = HashMap::<String, HashSet<usize>>::new();
+        for (index, test) in &tests {
+            origins.entry(test.file_name.clone()).or_default().insert(*index);
+        }
+
+        let tests = tests
+            .into_iter()
+            .map(|(index, mut test)| {
+                if origins[&test.file_name].len() > 1 {
+                    test.file_name = format!("{}-{}", namespaces[index], test.file_name);
+                }
+
+                test
+            })
+            .collect();
 
         Ok(Self(tests))
     }
@@ -310,6 +401,33 @@ impl Tests {
     }
 }
 
+/// Gets the offset and text of each markdown heading within a document.
+///
+/// Lines within fenced code blocks (e.g., WDL comments) are not headings.
+fn headings(contents: &str) -> Vec<(usize, String)> {
+    let mut headings = Vec::new();
+    let mut in_code_block = false;
+    let mut offset = 0;
+
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block && trimmed.starts_with('#') {
+            let heading = trimmed.trim_start_matches('#');
+
+            if heading.starts_with(' ') {
+                headings.push((offset, heading.trim().to_string()));
+            }
+        }
+
+        offset += line.len();
+    }
+
+    headings
+}
+
 /// Builds a conformance test from a set of captures.
 fn build_conformance_test(captures: Captures<'_>) -> Result<Test> {
     let file_name = required_string(&captures, 1, "filename")?;
@@ -317,6 +435,9 @@ fn build_conformance_test(captures: Captures<'_>) -> Result<Test> {
     let input = optional_json_group(&captures, 3);
     let output = optional_json_group(&captures, 4);
     let config = optional_group::<Config>(&captures, 5)?.unwrap_or_default();
+    config
+        .validate()
+        .with_context(|| format!("validating the config of test `{file_name}`"))?;
 
     Ok(Test::builder()
         .file_name(file_name)
@@ -364,6 +485,31 @@ where
         .transpose()
 }
 
+/// Creates the markdown of a conformance test example.
+///
+/// The output and configuration blocks are only included when provided.
+#[cfg(test)]
+pub(crate) fn example(name: &str, src: &str, output: Option<&str>, config: Option<&str>) -> String {
+    let mut example = format!("<details>\n<summary>\nExample: {name}\n\n```wdl\n{src}\n```\n</summary>\n");
+
+    if output.is_some() || config.is_some() {
+        example.push_str("<p>\n");
+
+        if let Some(output) = output {
+            example.push_str(&format!("Example output:\n\n```json\n{output}\n```\n\n"));
+        }
+
+        if let Some(config) = config {
+            example.push_str(&format!("Test config:\n\n```json\n{config}\n```\n"));
+        }
+
+        example.push_str("</p>\n");
+    }
+
+    example.push_str("</details>\n");
+    example
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -438,4 +584,55 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(captures.len(), 1);
     }
+
+    #[test]
+    fn compile_documents() {
+        let example = |name: &str| example(name, "version 1.2\nworkflow w {}", None, None);
+
+        let documents = [
+            Document::new("wdl/SPEC.md", example("hello.wdl") + &example("only_spec.wdl")),
+            Document::new("docs/extensions.md", example("hello.wdl")),
+        ];
+
+        let tests = Tests::compile_documents(&documents).unwrap();
+        let tests = tests
+            .tests()
+            .map(|test| (test.file_name(), test.origin().unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            tests,
+            [
+                ("SPEC-hello.wdl", "wdl/SPEC.md"),
+                ("only_spec.wdl", "wdl/SPEC.md"),
+                ("extensions-hello.wdl", "docs/extensions.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_documents_with_same_stem() {
+        let example = example("hello.wdl", "version 1.2\nworkflow w {}", None, None);
+
+        let documents = [
+            Document::new("a/SPEC.md", example.clone()),
+            Document::new("b/SPEC.md", example.clone()),
+            Document::new("b/SPEC.md", example),
+        ];
+
+        let tests = Tests::compile_documents(&documents).unwrap();
+        let names = tests.tests().map(Test::file_name).collect::<Vec<_>>();
+        assert_eq!(names, ["a-SPEC-hello.wdl", "b-SPEC-hello.wdl", "b-SPEC-2-hello.wdl"]);
+    }
+
+    #[test]
+    fn locations() {
+        let example = |name: &str| example(name, "version 1.2\n# Not a heading\nworkflow w {}", None, None);
+
+        let contents = ["Intro\n", &example("first.wdl"), "## Arrays\n\n", &example("second.wdl")].concat();
+        let tests = Tests::compile(contents).unwrap();
+        let tests = tests.tests().map(|test| (test.section(), test.line())).collect::<Vec<_>>();
+
+        assert_eq!(tests, [(None, Some(2)), (Some("Arrays"), Some(15))]);
+    }
 }
@@ -1,6 +1,9 @@
+use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
-use strum_macros::EnumIter;
+
+use crate::conformance::engine::limits::parse_memory;
+use crate::conformance::ResourceLimits;
 
 /// A tag associated with a conformance test.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -10,9 +13,16 @@ pub enum Tag {
     Deprecated,
 }
 
+/// The separator between the namespace and the name of a custom capability.
+const CAPABILITY_NAMESPACE_SEPARATOR: char = ':';
+
 /// A capability required by a conformance test.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, EnumIter)]
-#[serde(rename_all = "snake_case")]
+///
+/// Capabilities not known to this version of `spectool` are kept as custom
+/// capabilities. Custom capabilities are expected to be namespaced (e.g.,
+/// `sprocket:network`) so they cannot collide with capabilities added to the
+/// specification later.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Capability {
     /// Requires specific CPU resources.
     Cpu,
@@ -23,8 +33,85 @@ pub enum Capability {
     /// Requires specific disk resources.
     Disks,
     /// Allows setting nested workflow/task inputs at runtime.
-    #[value(name = "allow_nested_inputs")]
     AllowNestedInputs,
+    /// A capability that is not known to this version of `spectool`.
+    Custom(String),
+}
+
+impl Capability {
+    /// The capabilities known to this version of `spectool`.
+    pub const KNOWN: &[Capability] = &[
+        Capability::Cpu,
+        Capability::Memory,
+        Capability::Gpu,
+        Capability::Disks,
+        Capability::AllowNestedInputs,
+    ];
+
+    /// Returns whether the capability is known to this version of `spectool`.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Capability::Custom(_))
+    }
+
+    /// Gets the namespace of a custom capability, if it has one.
+    pub fn namespace(&self) -> Option<&str> {
+        match self {
+            Capability::Custom(name) => name
+                .split_once(CAPABILITY_NAMESPACE_SEPARATOR)
+                .map(|(namespace, _)| namespace),
+            _ => None,
+        }
+    }
+
+    /// Gets a lint warning for the capability, if any.
+    ///
+    /// Custom capabilities without a namespace are either from a newer
+    /// version of the specification or are missing a namespace.
+    pub fn lint(&self) -> Option<String> {
+        match self {
+            Capability::Custom(name) if self.namespace().is_none() => Some(format!(
+                "unknown capability `{name}` (it may be from a newer version of the specification; custom \
+                 capabilities should be namespaced, e.g., `vendor{CAPABILITY_NAMESPACE_SEPARATOR}{name}`)"
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for Capability {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cpu" => Ok(Capability::Cpu),
+            "memory" => Ok(Capability::Memory),
+            "gpu" => Ok(Capability::Gpu),
+            "disks" => Ok(Capability::Disks),
+            "allow_nested_inputs" => Ok(Capability::AllowNestedInputs),
+            _ => {
+                let is_valid = |part: &str| {
+                    !part.is_empty()
+                        && part
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+                };
+
+                let valid = match s.split_once(CAPABILITY_NAMESPACE_SEPARATOR) {
+                    Some((namespace, name)) => is_valid(namespace) && is_valid(name),
+                    None => is_valid(s),
+                };
+
+                if !valid {
+                    return Err(format!(
+                        "invalid capability `{s}` (expected a name or `namespace{CAPABILITY_NAMESPACE_SEPARATOR}name` \
+                         made up of alphanumeric characters, `_`, `-`, or `.`)"
+                    ));
+                }
+
+                Ok(Capability::Custom(s.to_string()))
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for Capability {
@@ -35,15 +122,66 @@ impl std::fmt::Display for Capability {
             Capability::Gpu => write!(f, "gpu"),
             Capability::Disks => write!(f, "disks"),
             Capability::AllowNestedInputs => write!(f, "allow_nested_inputs"),
+            Capability::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Serialize for Capability {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Capability {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The phase in which a conformance test is expected to fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailPhase {
+    /// The document fails to parse.
+    Parse,
+    /// The document fails static analysis (e.g., a type error).
+    Analysis,
+    /// The document fails during execution.
+    Runtime,
+}
+
+impl FailPhase {
+    /// Returns whether the failure is expected to be caught without executing
+    /// the document.
+    pub fn is_static(&self) -> bool {
+        matches!(self, FailPhase::Parse | FailPhase::Analysis)
+    }
+}
+
+impl std::fmt::Display for FailPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailPhase::Parse => write!(f, "parse"),
+            FailPhase::Analysis => write!(f, "analysis"),
+            FailPhase::Runtime => write!(f, "runtime"),
         }
     }
 }
 
 /// The expected return code(s) for a conformance test.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ReturnCode {
     /// Any return code is allowed.
+    #[default]
     #[serde(deserialize_with = "deserialize_any")]
     Any,
     /// A single expected return code.
@@ -52,12 +190,6 @@ pub enum ReturnCode {
     Multiple(Vec<i32>),
 }
 
-impl Default for ReturnCode {
-    fn default() -> Self {
-        ReturnCode::Any
-    }
-}
-
 /// Custom deserializer for the "*" string to represent Any.
 fn deserialize_any<'de, D>(deserializer: D) -> Result<(), D::Error>
 where
@@ -71,6 +203,77 @@ where
     }
 }
 
+/// A regular expression matched against a captured output stream.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Gets the source of the regular expression.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns whether the pattern matches anywhere within `haystack`.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.0.is_match(haystack)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Regex::new(&s)
+            .map(Pattern)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Custom deserializer for a memory size given as a number of bytes or a
+/// string with a suffix (e.g., `"512M"`).
+fn deserialize_memory<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Memory {
+        Bytes(u64),
+        Size(String),
+    }
+
+    match Option::<Memory>::deserialize(deserializer)? {
+        Some(Memory::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(Memory::Size(size)) => parse_memory(&size).map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
 /// A configuration for a conformance test.
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -89,6 +292,18 @@ pub struct Config {
     #[serde(default)]
     fail: bool,
 
+    /// The phase in which the test is expected to fail.
+    fail_phase: Option<FailPhase>,
+
+    /// Whether an engine terminated by a signal satisfies `fail: true`.
+    ///
+    /// Crashes of the check command never satisfy a static `fail_phase`.
+    /// Signals are only reported reliably with `--exec`, as `bash` reports a
+    /// signal within a pipeline or compound command as an exit code of
+    /// `128 + N`.
+    #[serde(default)]
+    allow_crash: bool,
+
     /// The expected return code(s).
     #[serde(default)]
     return_code: ReturnCode,
@@ -104,9 +319,36 @@ pub struct Config {
     /// Tags associated with the test (e.g., deprecated).
     #[serde(default)]
     tags: Vec<Tag>,
+
+    /// A pattern the captured stdout is expected to match.
+    expected_stdout: Option<Pattern>,
+
+    /// A pattern the captured stderr is expected to match.
+    expected_stderr: Option<Pattern>,
+
+    /// The maximum virtual memory of the engine process in bytes.
+    #[serde(default, deserialize_with = "deserialize_memory")]
+    max_memory: Option<u64>,
+
+    /// The maximum CPU time of the engine process in seconds.
+    max_cpu_time: Option<u64>,
+
+    /// The maximum number of open files of the engine process.
+    max_open_files: Option<u64>,
 }
 
 impl Config {
+    /// Validates the combination of keys within the configuration.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(phase) = self.fail_phase {
+            if !self.fail {
+                anyhow::bail!("`fail_phase` is `{phase}` but the test is not marked with `fail: true`");
+            }
+        }
+
+        Ok(())
+    }
+
     /// Gets the target workflow or task name.
     pub fn target(&self) -> Option<&str> {
         self.target.as_deref()
@@ -122,6 +364,16 @@ impl Config {
         self.fail
     }
 
+    /// Returns whether an engine terminated by a signal satisfies `fail: true`.
+    pub fn allow_crash(&self) -> bool {
+        self.allow_crash
+    }
+
+    /// Gets the phase in which the test is expected to fail.
+    pub fn fail_phase(&self) -> Option<FailPhase> {
+        self.fail_phase
+    }
+
     /// Gets the expected return code(s).
     pub fn return_code(&self) -> &ReturnCode {
         &self.return_code
@@ -141,6 +393,25 @@ impl Config {
     pub fn tags(&self) -> &[Tag] {
         &self.tags
     }
+
+    /// Gets the pattern the captured stdout is expected to match.
+    pub fn expected_stdout(&self) -> Option<&Pattern> {
+        self.expected_stdout.as_ref()
+    }
+
+    /// Gets the pattern the captured stderr is expected to match.
+    pub fn expected_stderr(&self) -> Option<&Pattern> {
+        self.expected_stderr.as_ref()
+    }
+
+    /// Gets the resource limits for the engine process.
+    pub fn limits(&self) -> ResourceLimits {
+        ResourceLimits::builder()
+            .maybe_memory(self.max_memory)
+            .maybe_cpu_time(self.max_cpu_time)
+            .maybe_open_files(self.max_open_files)
+            .build()
+    }
 }
 
 #[cfg(test)]
@@ -153,12 +424,17 @@ mod tests {
         let config: Config = serde_json::from_str(json).unwrap();
 
         assert_eq!(config.target(), None);
-        assert_eq!(config.ignore(), false);
-        assert_eq!(config.fail(), false);
+        assert!(!config.ignore());
+        assert!(!config.fail());
+        assert_eq!(config.fail_phase(), None);
+        assert!(!config.allow_crash());
         assert_eq!(config.return_code(), &ReturnCode::Any);
         assert_eq!(config.exclude_outputs(), &[] as &[String]);
         assert_eq!(config.capabilities(), &[] as &[Capability]);
         assert_eq!(config.tags(), &[] as &[Tag]);
+        assert!(config.expected_stdout().is_none());
+        assert!(config.expected_stderr().is_none());
+        assert!(config.limits().is_empty());
     }
 
     #[test]
@@ -195,6 +471,8 @@ mod tests {
             "target": "my_task",
             "ignore": true,
             "fail": true,
+            "fail_phase": "analysis",
+            "allow_crash": true,
             "return_code": 1,
             "exclude_outputs": ["timestamp"],
             "capabilities": ["cpu", "gpu"],
@@ -203,8 +481,10 @@ mod tests {
         let config: Config = serde_json::from_str(json).unwrap();
 
         assert_eq!(config.target(), Some("my_task"));
-        assert_eq!(config.ignore(), true);
-        assert_eq!(config.fail(), true);
+        assert!(config.ignore());
+        assert!(config.fail());
+        assert_eq!(config.fail_phase(), Some(FailPhase::Analysis));
+        assert!(config.allow_crash());
         assert_eq!(config.return_code(), &ReturnCode::Single(1));
         assert_eq!(config.exclude_outputs(), &["timestamp"]);
         assert_eq!(config.capabilities(), &[Capability::Cpu, Capability::Gpu]);
@@ -219,16 +499,91 @@ mod tests {
     }
 
     #[test]
-    fn unknown_field_rejected() {
-        let json = r#"{"unknown_field": "value"}"#;
+    fn fail_phases() {
+        for (value, phase) in [
+            ("parse", FailPhase::Parse),
+            ("analysis", FailPhase::Analysis),
+            ("runtime", FailPhase::Runtime),
+        ] {
+            let json = format!(r#"{{"fail": true, "fail_phase": "{value}"}}"#);
+            let config: Config = serde_json::from_str(&json).unwrap();
+            assert_eq!(config.fail_phase(), Some(phase));
+        }
+
+        let config: Config = serde_json::from_str(r#"{"fail_phase": "parse"}"#).unwrap();
+        assert!(config.validate().is_err());
+
+        let result: Result<Config, _> = serde_json::from_str(r#"{"fail_phase": "link"}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn expected_streams() {
+        let json = r#"{"expected_stdout": "^done$", "expected_stderr": "(?i)type error"}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.expected_stdout().unwrap().as_str(), "^done$");
+        assert!(config.expected_stderr().unwrap().is_match("error: Type Error at line 3"));
+        assert!(!config.expected_stderr().unwrap().is_match("panicked at src/main.rs"));
+    }
+
+    #[test]
+    fn invalid_expected_stream_pattern_rejected() {
+        let json = r#"{"expected_stderr": "(unclosed"}"#;
         let result: Result<Config, _> = serde_json::from_str(json);
         assert!(result.is_err());
     }
 
     #[test]
-    fn unknown_capability_rejected() {
-        let json = r#"{"capabilities": ["unknown"]}"#;
+    fn limits() {
+        let json = r#"{"max_memory": "512M", "max_cpu_time": 60, "max_open_files": 256}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.limits().memory(), Some(512 << 20));
+        assert_eq!(config.limits().cpu_time(), Some(60));
+        assert_eq!(config.limits().open_files(), Some(256));
+
+        let config: Config = serde_json::from_str(r#"{"max_memory": 1024}"#).unwrap();
+        assert_eq!(config.limits().memory(), Some(1024));
+
+        let result: Result<Config, _> = serde_json::from_str(r#"{"max_memory": "lots"}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unknown_field_rejected() {
+        let json = r#"{"unknown_field": "value"}"#;
         let result: Result<Config, _> = serde_json::from_str(json);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn custom_capabilities() {
+        let json = r#"{"capabilities": ["fpga", "sprocket:network", "cpu"]}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.capabilities(),
+            &[
+                Capability::Custom("fpga".to_string()),
+                Capability::Custom("sprocket:network".to_string()),
+                Capability::Cpu
+            ]
+        );
+
+        let [fpga, network, cpu] = config.capabilities() else {
+            panic!("expected three capabilities");
+        };
+        assert!(fpga.lint().is_some());
+        assert_eq!(network.namespace(), Some("sprocket"));
+        assert!(network.lint().is_none());
+        assert!(cpu.is_known());
+        assert_eq!(network.to_string(), "sprocket:network");
+    }
+
+    #[test]
+    fn invalid_capability_rejected() {
+        for capability in ["", "has space", "a:b:c", ":network", "sprocket:"] {
+            let json = format!(r#"{{"capabilities": ["{capability}"]}}"#);
+            let result: Result<Config, _> = serde_json::from_str(&json);
+            assert!(result.is_err(), "`{capability}` should be rejected");
+        }
+    }
 }
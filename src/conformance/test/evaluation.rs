@@ -0,0 +1,460 @@
+use std::path::Path;
+
+use bon::builder;
+
+use crate::conformance::Execution;
+use crate::conformance::test::selector;
+use crate::conformance::test::style;
+use crate::conformance::test::validation::validate_outputs;
+use crate::conformance::Engine;
+use crate::conformance::FailPhase;
+use crate::conformance::FailureReason;
+use crate::conformance::KeyStyle;
+use crate::conformance::Limit;
+use crate::conformance::ReturnCode;
+use crate::conformance::Stream;
+use crate::conformance::Test;
+use crate::conformance::TestResult;
+
+/// The evaluation of a conformance test.
+#[derive(Debug)]
+pub struct Evaluation {
+    /// The result of the test.
+    result: TestResult,
+
+    /// The last execution performed by the engine, if any.
+    execution: Option<Execution>,
+}
+
+impl Evaluation {
+    /// Gets the result of the test.
+    pub fn result(&self) -> &TestResult {
+        &self.result
+    }
+
+    /// Gets the last execution performed by the engine.
+    ///
+    /// This is the check of the document if evaluation stopped after it.
+    pub fn execution(&self) -> Option<&Execution> {
+        self.execution.as_ref()
+    }
+
+    /// Consumes `self` and returns the result of the test.
+    pub fn into_result(self) -> TestResult {
+        self.result
+    }
+}
+
+/// Executes a test with an engine and evaluates the result.
+///
+/// If the test is expected to fail in a specific phase and the engine
+/// supports checking documents, the document is checked before it is
+/// executed.
+///
+/// The outputs are passed through the output selector and then normalized
+/// from the output style, if either is provided, before being validated.
+#[builder]
+pub fn evaluate(
+    test: &Test,
+    engine: &dyn Engine,
+    input: &Path,
+    workdir: &Path,
+    output_selector: Option<&str>,
+    output_style: Option<KeyStyle>,
+) -> Evaluation {
+    // Determine if test should have failed and, if so, in which phase
+    let expected_to_fail = test.config().fail();
+    let mut fail_phase = test.config().fail_phase().filter(|_| expected_to_fail);
+
+    // If the failure phase matters, validate the document before executing it
+    if let Some(phase) = fail_phase {
+        match engine.check(test, input, workdir) {
+            Some(Ok(check)) => {
+                if let Some(result) = evaluate_check(test, &check, phase) {
+                    return Evaluation {
+                        result,
+                        execution: Some(check),
+                    };
+                }
+            }
+            Some(Err(failure_reason)) => {
+                return Evaluation {
+                    result: TestResult::Failed(failure_reason),
+                    execution: None,
+                };
+            }
+            // The phase cannot be verified without checking the document
+            None => fail_phase = None,
+        }
+    }
+
+    // Execute the test
+    let execution = match engine.execute(test, input, workdir) {
+        Ok(execution) => execution,
+        Err(failure_reason) => {
+            return Evaluation {
+                result: TestResult::Failed(failure_reason),
+                execution: None,
+            };
+        }
+    };
+
+    let result = evaluate_execution(test, &execution, fail_phase, output_selector, output_style);
+
+    Evaluation {
+        result,
+        execution: Some(execution),
+    }
+}
+
+/// Evaluates the check of a document that is expected to fail in a phase.
+///
+/// Returns `None` if the document passed the check, in which case the test
+/// is still to be executed.
+fn evaluate_check(test: &Test, check: &Execution, phase: FailPhase) -> Option<TestResult> {
+    // Exceeding a resource limit is never an expected failure
+    if let Some(limit) = exceeded_limit(test, check) {
+        return Some(TestResult::Failed(FailureReason::LimitExceeded(limit)));
+    }
+
+    // NOTE: a crash of the check command is never a static rejection of the
+    // document, even if the test allows crashes.
+    if let Some(signal) = check.signal() {
+        return Some(TestResult::Failed(FailureReason::Signaled {
+            signal,
+            core_dumped: check.core_dumped(),
+        }));
+    }
+
+    if check.exit_code() == 0 {
+        return None;
+    }
+
+    if !phase.is_static() {
+        return Some(TestResult::Failed(FailureReason::FailPhaseMismatch {
+            expected: phase,
+            caught_statically: true,
+        }));
+    }
+
+    match check_streams(test, check) {
+        Ok(()) => Some(TestResult::Passed),
+        Err(failure_reason) => Some(TestResult::Failed(failure_reason)),
+    }
+}
+
+/// Evaluates the execution of a test.
+fn evaluate_execution(
+    test: &Test,
+    execution: &Execution,
+    fail_phase: Option<FailPhase>,
+    output_selector: Option<&str>,
+    output_style: Option<KeyStyle>,
+) -> TestResult {
+    let expected_to_fail = test.config().fail();
+    let exit_code = execution.exit_code();
+
+    // Exceeding a resource limit is never an expected failure
+    if let Some(limit) = exceeded_limit(test, execution) {
+        return TestResult::Failed(FailureReason::LimitExceeded(limit));
+    }
+
+    // A crash never satisfies a test unless it is expected to fail and
+    // explicitly allows it
+    if let Some(signal) = execution.signal() {
+        if !(expected_to_fail && test.config().allow_crash()) {
+            return TestResult::Failed(FailureReason::Signaled {
+                signal,
+                core_dumped: execution.core_dumped(),
+            });
+        }
+    }
+
+    // If test is expected to fail, check if command failed (non-zero exit)
+    // in the expected phase with the expected error
+    if expected_to_fail {
+        if exit_code == 0 {
+            return TestResult::Failed(FailureReason::UnexpectedSuccess);
+        } else if let Some(phase) = fail_phase.filter(|phase| phase.is_static()) {
+            return TestResult::Failed(FailureReason::FailPhaseMismatch {
+                expected: phase,
+                caught_statically: false,
+            });
+        } else if let Err(failure_reason) = check_streams(test, execution) {
+            return TestResult::Failed(failure_reason);
+        } else {
+            return TestResult::Passed;
+        }
+    }
+
+    // Check return code
+    let return_code_matches = match test.config().return_code() {
+        ReturnCode::Any => true,
+        ReturnCode::Single(expected) => exit_code == *expected,
+        ReturnCode::Multiple(expected) => expected.contains(&exit_code),
+    };
+
+    // If return code doesn't match, test failed
+    if !return_code_matches {
+        return TestResult::Failed(FailureReason::ReturnCodeMismatch {
+            expected: test.config().return_code().clone(),
+            actual: exit_code,
+        });
+    }
+
+    // Check the captured streams against any expected patterns
+    if let Err(failure_reason) = check_streams(test, execution) {
+        return TestResult::Failed(failure_reason);
+    }
+
+    // If we have expected output, validate it
+    if let Some(expected_output) = test.output() {
+        let Some(actual_output) = execution.outputs() else {
+            return TestResult::Failed(FailureReason::NoOutput);
+        };
+
+        // Apply output selector if provided
+        let actual_output = if let Some(selector) = output_selector {
+            match selector::apply(selector, actual_output) {
+                Ok(transformed) => transformed,
+                Err(failure_reason) => return TestResult::Failed(failure_reason),
+            }
+        } else {
+            actual_output.clone()
+        };
+
+        // Normalize the output keys if a style is provided
+        let actual_output = if let Some(output_style) = output_style {
+            let target = test.target().expect("target should be inferred");
+            match style::flatten(&actual_output, output_style, target.name()) {
+                Ok(flattened) => flattened,
+                Err(e) => {
+                    return TestResult::Failed(FailureReason::OutputMismatch {
+                        details: format!("failed to normalize `{}` outputs: {}", output_style, e),
+                    });
+                }
+            }
+        } else {
+            actual_output
+        };
+
+        if let Err(e) = validate_outputs(
+            expected_output,
+            &actual_output,
+            test.config().exclude_outputs(),
+        ) {
+            return TestResult::Failed(FailureReason::OutputMismatch {
+                details: e.to_string(),
+            });
+        }
+    }
+
+    TestResult::Passed
+}
+
+/// Gets the resource limit exceeded by an execution, if any.
+///
+/// Exceeding the memory or open files limit is inferred from the error
+/// reported to stderr, so it is disregarded when the test is expected to fail
+/// with an error matching its `expected_stderr` pattern.
+fn exceeded_limit(test: &Test, execution: &Execution) -> Option<Limit> {
+    let limit = execution.exceeded_limit()?;
+
+    let inferred_from_stderr = matches!(limit, Limit::Memory(_) | Limit::OpenFiles(_));
+    let expected_error = test.config().fail()
+        && test.config().expected_stderr().is_some()
+        && check_streams(test, execution).is_ok();
+
+    if inferred_from_stderr && expected_error {
+        return None;
+    }
+
+    Some(limit)
+}
+
+/// Checks the captured stdout and stderr against the patterns in the test
+/// configuration.
+fn check_streams(test: &Test, execution: &Execution) -> Result<(), FailureReason> {
+    let streams = [
+        (Stream::Stdout, test.config().expected_stdout(), execution.stdout()),
+        (Stream::Stderr, test.config().expected_stderr(), execution.stderr()),
+    ];
+
+    for (stream, pattern, contents) in streams {
+        if let Some(pattern) = pattern {
+            if !pattern.is_match(&String::from_utf8_lossy(contents)) {
+                return Err(FailureReason::StreamMismatch {
+                    stream,
+                    pattern: pattern.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::conformance::test::Config;
+
+    /// An in-process engine that returns a fixed execution.
+    struct FixedEngine(Execution);
+
+    impl Engine for FixedEngine {
+        fn execute(&self, _: &Test, _: &Path, _: &Path) -> Result<Execution, FailureReason> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// An in-process engine that returns a fixed execution for both checks
+    /// and executions.
+    struct CheckingEngine(Execution);
+
+    impl Engine for CheckingEngine {
+        fn execute(&self, _: &Test, _: &Path, _: &Path) -> Result<Execution, FailureReason> {
+            Ok(self.0.clone())
+        }
+
+        fn check(&self, _: &Test, _: &Path, _: &Path) -> Option<Result<Execution, FailureReason>> {
+            Some(Ok(self.0.clone()))
+        }
+    }
+
+    fn test(config: &str) -> Test {
+        let mut test = Test::builder()
+            .file_name("hello.wdl".to_string())
+            .src("version 1.2\nworkflow hello {}".to_string())
+            .output(json!({"hello.out": "hi"}))
+            .config(serde_json::from_str::<Config>(config).unwrap())
+            .build();
+        test.infer_and_validate_target().unwrap();
+        test
+    }
+
+    fn evaluate_with(test: &Test, execution: Execution, output_style: Option<KeyStyle>) -> TestResult {
+        evaluate()
+            .test(test)
+            .engine(&FixedEngine(execution))
+            .input(Path::new("inputs.json"))
+            .workdir(Path::new("."))
+            .maybe_output_style(output_style)
+            .call()
+            .into_result()
+    }
+
+    #[test]
+    fn passes() {
+        let execution = Execution::builder().exit_code(0).outputs(json!({"hello.out": "hi"})).build();
+        assert_eq!(evaluate_with(&test("{}"), execution, None), TestResult::Passed);
+    }
+
+    #[test]
+    fn normalizes_outputs() {
+        let execution = Execution::builder().exit_code(0).outputs(json!({"out": "hi"})).build();
+        let result = evaluate_with(&test("{}"), execution, Some(KeyStyle::Unprefixed));
+        assert_eq!(result, TestResult::Passed);
+    }
+
+    #[test]
+    fn no_output() {
+        let execution = Execution::builder().exit_code(0).build();
+        let result = evaluate_with(&test("{}"), execution, None);
+        assert_eq!(result, TestResult::Failed(FailureReason::NoOutput));
+    }
+
+    #[test]
+    fn unexpected_success() {
+        let execution = Execution::builder().exit_code(0).build();
+        let result = evaluate_with(&test(r#"{"fail": true}"#), execution, None);
+        assert_eq!(result, TestResult::Failed(FailureReason::UnexpectedSuccess));
+    }
+
+    #[test]
+    fn wrong_error() {
+        let config = r#"{"fail": true, "expected_stderr": "type mismatch"}"#;
+
+        let execution = Execution::builder()
+            .exit_code(1)
+            .stderr(b"error: type mismatch".to_vec())
+            .build();
+        assert_eq!(evaluate_with(&test(config), execution, None), TestResult::Passed);
+
+        let execution = Execution::builder()
+            .exit_code(101)
+            .stderr(b"thread 'main' panicked".to_vec())
+            .build();
+        let result = evaluate_with(&test(config), execution, None);
+        assert!(matches!(
+            result,
+            TestResult::Failed(FailureReason::StreamMismatch { stream: Stream::Stderr, .. })
+        ));
+    }
+
+    #[test]
+    fn crash_does_not_satisfy_fail() {
+        let execution = Execution::builder().exit_code(-1).signal(11).core_dumped(true).build();
+        let result = evaluate_with(&test(r#"{"fail": true}"#), execution.clone(), None);
+        assert_eq!(
+            result,
+            TestResult::Failed(FailureReason::Signaled {
+                signal: 11,
+                core_dumped: true
+            })
+        );
+
+        let result = evaluate_with(&test(r#"{"fail": true, "allow_crash": true}"#), execution, None);
+        assert_eq!(result, TestResult::Passed);
+    }
+
+    #[test]
+    fn crashed_check_is_not_static_rejection() {
+        let test = test(r#"{"fail": true, "fail_phase": "analysis", "allow_crash": true}"#);
+        let execution = Execution::builder().exit_code(-1).signal(9).build();
+
+        let result = evaluate()
+            .test(&test)
+            .engine(&CheckingEngine(execution))
+            .input(Path::new("inputs.json"))
+            .workdir(Path::new("."))
+            .call()
+            .into_result();
+        assert_eq!(
+            result,
+            TestResult::Failed(FailureReason::Signaled {
+                signal: 9,
+                core_dumped: false
+            })
+        );
+    }
+
+    #[test]
+    fn limit_exceeded() {
+        let execution = Execution::builder()
+            .exit_code(1)
+            .exceeded_limit(Limit::Memory(1024))
+            .build();
+        let result = evaluate_with(&test(r#"{"fail": true}"#), execution, None);
+        assert_eq!(result, TestResult::Failed(FailureReason::LimitExceeded(Limit::Memory(1024))));
+
+        // An error that the test expects is not attributed to the limit
+        let execution = Execution::builder()
+            .exit_code(1)
+            .stderr(b"MemoryError: array too large".to_vec())
+            .exceeded_limit(Limit::Memory(1024))
+            .build();
+        let config = r#"{"fail": true, "expected_stderr": "MemoryError"}"#;
+        assert_eq!(evaluate_with(&test(config), execution, None), TestResult::Passed);
+    }
+
+    #[test]
+    fn unchecked_fail_phase() {
+        // Engines that cannot check documents do not verify the phase
+        let execution = Execution::builder().exit_code(1).build();
+        let result = evaluate_with(&test(r#"{"fail": true, "fail_phase": "parse"}"#), execution, None);
+        assert_eq!(result, TestResult::Passed);
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 
 use crate::conformance::Capability;
+use crate::conformance::FailPhase;
+use crate::conformance::Limit;
 use crate::conformance::test::ReturnCode;
 
 /// The result of running a conformance test.
@@ -31,6 +33,23 @@ pub enum FailureReason {
     },
     /// The command execution failed with an error.
     ExecutionError(String),
+    /// The inputs could not be prepared for the command.
+    InputError(String),
+    /// The command was terminated by a signal.
+    ///
+    /// When a command is run through `bash`, a signal that terminates a
+    /// process within a pipeline or compound command is only seen as an exit
+    /// code of `128 + N`, so such crashes are reported as ordinary failures.
+    /// Signals are reported reliably when the engine is spawned directly
+    /// (`--exec`).
+    Signaled {
+        /// The signal number.
+        signal: i32,
+        /// Whether a core dump was produced.
+        core_dumped: bool,
+    },
+    /// The command exceeded a resource limit.
+    LimitExceeded(Limit),
     /// The test was expected to fail but succeeded.
     UnexpectedSuccess,
     /// No output was produced by the command.
@@ -42,6 +61,29 @@ pub enum FailureReason {
         /// Details about the error.
         details: String,
     },
+    /// The test failed, but not in the expected phase.
+    FailPhaseMismatch {
+        /// The phase in which the test was expected to fail.
+        expected: FailPhase,
+        /// Whether the failure was caught by the check command.
+        caught_statically: bool,
+    },
+    /// A captured output stream did not match the expected pattern.
+    StreamMismatch {
+        /// The stream that was checked.
+        stream: Stream,
+        /// The pattern the stream was expected to match.
+        pattern: String,
+    },
+}
+
+/// A captured output stream of an execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    /// The standard output stream.
+    Stdout,
+    /// The standard error stream.
+    Stderr,
 }
 
 /// The reason a test was skipped.
@@ -82,6 +124,19 @@ impl fmt::Display for FailureReason {
             FailureReason::ExecutionError(e) => {
                 write!(f, "execution error: {}", e)
             }
+            FailureReason::InputError(e) => {
+                write!(f, "input error: {}", e)
+            }
+            FailureReason::Signaled { signal, core_dumped } => {
+                write!(f, "terminated by signal {}", signal)?;
+                if *core_dumped {
+                    write!(f, " (core dumped)")?;
+                }
+                Ok(())
+            }
+            FailureReason::LimitExceeded(limit) => {
+                write!(f, "exceeded the {}", limit)
+            }
             FailureReason::UnexpectedSuccess => {
                 write!(f, "test marked with `fail: true` but succeeded")
             }
@@ -91,6 +146,25 @@ impl fmt::Display for FailureReason {
             FailureReason::SelectorError { selector, details } => {
                 write!(f, "selector error for `{}`: {}", selector, details)
             }
+            FailureReason::FailPhaseMismatch { expected, caught_statically } => {
+                if *caught_statically {
+                    write!(f, "expected failure during `{}`, but the check command rejected the document", expected)
+                } else {
+                    write!(f, "expected failure during `{}`, but the check command accepted the document", expected)
+                }
+            }
+            FailureReason::StreamMismatch { stream, pattern } => {
+                write!(f, "{} did not match expected pattern `{}`", stream, pattern)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stream::Stdout => write!(f, "stdout"),
+            Stream::Stderr => write!(f, "stderr"),
         }
     }
 }
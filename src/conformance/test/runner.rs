@@ -43,7 +43,38 @@ impl Runner {
         inject_wdl_version: Option<String>,
     ) -> Result<Self> {
         let contents = contents.as_ref();
+        let resources = conformance::Resources::compile(contents)?;
+        let tests = conformance::Tests::compile(contents)?;
+
+        Self::write(root_dir, resources, tests, force, inject_wdl_version)
+    }
+
+    /// Compiles conformance tests from several markdown documents into a
+    /// single suite.
+    ///
+    /// See [`conformance::Tests::compile_documents`] for how tests with the
+    /// same name are handled.
+    pub fn compile_documents(
+        root_dir: PathBuf,
+        documents: &[conformance::Document],
+        force: bool,
+        inject_wdl_version: Option<String>,
+    ) -> Result<Self> {
+        let resources = conformance::Resources::compile_documents(documents)?;
+        let tests = conformance::Tests::compile_documents(documents)?;
+
+        Self::write(root_dir, resources, tests, force, inject_wdl_version)
+    }
 
+    /// Writes the resources and conformance tests to the conformance tests
+    /// directory.
+    fn write(
+        root_dir: PathBuf,
+        resources: conformance::Resources,
+        mut tests: conformance::Tests,
+        force: bool,
+        inject_wdl_version: Option<String>,
+    ) -> Result<Self> {
         //=========================================//
         // Prepare the conformance tests directory //
         //=========================================//
@@ -63,11 +94,9 @@ impl Runner {
         std::fs::create_dir_all(&data_dir)
             .context("creating `data` directory")?;
 
-        //================================//
-        // Gather and write the resources //
-        //================================//
-
-        let resources = conformance::Resources::compile(contents)?;
+        //=====================//
+        // Write the resources //
+        //=====================//
 
         for resource in resources.iter() {
             let file_path = data_dir.join(resource.filename());
@@ -87,11 +116,9 @@ impl Runner {
                 .with_context(|| format!("writing `{}` resource file", resource.filename()))?;
         }
 
-        //===============================//
-        // Compile the conformance tests //
-        //===============================//
-
-        let mut tests = conformance::Tests::compile(contents)?;
+        //=============================//
+        // Write the conformance tests //
+        //=============================//
 
         for test in tests.tests_mut() {
             // Infer and validate the target before writing the test
@@ -131,6 +158,23 @@ impl Runner {
     pub fn tests(&self) -> impl Iterator<Item = &conformance::Test> {
         self.tests.tests()
     }
+
+    /// Gets the lint warnings for the tests within the runner.
+    ///
+    /// These are not emitted as logs so that callers may report them
+    /// regardless of the verbosity.
+    pub fn lints(&self) -> Vec<String> {
+        self.tests
+            .tests()
+            .flat_map(|test| {
+                test.config()
+                    .capabilities()
+                    .iter()
+                    .filter_map(conformance::Capability::lint)
+                    .map(move |lint| format!("test `{}` requires an {}", test.file_name(), lint))
+            })
+            .collect()
+    }
 }
 
 /// Ensures that the directory exists and is empty.
@@ -0,0 +1,106 @@
+use jaq_core::data;
+use jaq_core::load::Arena;
+use jaq_core::load::File;
+use jaq_core::load::Loader;
+use jaq_core::unwrap_valr;
+use jaq_core::Compiler;
+use jaq_core::Ctx;
+use jaq_core::Vars;
+use jaq_json::Val;
+use serde_json::Value;
+
+use crate::conformance::FailureReason;
+
+/// Applies a `jq` selector to a JSON value.
+pub fn apply(selector: &str, input: &Value) -> Result<Value, FailureReason> {
+    let program = File { code: selector, path: () };
+    let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+    let arena = Arena::default();
+
+    // Parse the selector
+    let modules = loader.load(&arena, program).map_err(|errs| {
+        let error_msg = errs.into_iter().map(|(file, err)| format!("{}: {:?}", file.code, err)).collect::<Vec<_>>().join("; ");
+        FailureReason::SelectorError {
+            selector: selector.to_string(),
+            details: error_msg,
+        }
+    })?;
+
+    // Compile the selector
+    let filter = Compiler::default()
+        .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+        .compile(modules)
+        .map_err(|errs| {
+            let error_msg = errs.into_iter().map(|(file, err)| {
+                let err_str = err.into_iter().map(|(name, _)| name).collect::<Vec<_>>().join(", ");
+                format!("{}: undefined: {}", file.code, err_str)
+            }).collect::<Vec<_>>().join("; ");
+            FailureReason::SelectorError {
+                selector: selector.to_string(),
+                details: error_msg,
+            }
+        })?;
+
+    // Convert `serde_json::Value` to `jaq` `Val` using JSON string roundtrip
+    let json_str = input.to_string();
+    let jaq_input = jaq_json::read::parse_single(json_str.as_bytes()).map_err(|e| FailureReason::SelectorError {
+        selector: selector.to_string(),
+        details: format!("failed to parse input as JSON: {}", e),
+    })?;
+
+    // Execute the selector
+    let ctx = Ctx::<data::JustLut<Val>>::new(&filter.lut, Vars::new([]));
+    let mut outputs = filter.id.run((ctx, jaq_input)).map(unwrap_valr);
+
+    // Expect exactly one output
+    let first_output = outputs.next();
+    let second_output = outputs.next();
+
+    match (first_output, second_output) {
+        (None, _) => Err(FailureReason::SelectorError {
+            selector: selector.to_string(),
+            details: "selector produced no output".to_string(),
+        }),
+        (Some(Err(e)), _) => Err(FailureReason::SelectorError {
+            selector: selector.to_string(),
+            details: format!("selector execution failed: {}", e),
+        }),
+        (Some(Ok(_)), Some(_)) => Err(FailureReason::SelectorError {
+            selector: selector.to_string(),
+            details: "selector produced multiple outputs (expected exactly one)".to_string(),
+        }),
+        (Some(Ok(val)), None) => {
+            let json_str = val.to_string();
+            serde_json::from_str(&json_str).map_err(|e| FailureReason::SelectorError {
+                selector: selector.to_string(),
+                details: format!("failed to convert result to JSON: {}", e),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn select_field() {
+        let input = json!({"outputs": {"hello.matches": ["hello world"]}});
+        let output = apply(".outputs", &input).unwrap();
+        assert_eq!(output, json!({"hello.matches": ["hello world"]}));
+    }
+
+    #[test]
+    fn multiple_outputs() {
+        let input = json!([1, 2]);
+        let result = apply(".[]", &input);
+        assert!(matches!(result, Err(FailureReason::SelectorError { .. })));
+    }
+
+    #[test]
+    fn invalid_selector() {
+        let result = apply(".[", &json!({}));
+        assert!(matches!(result, Err(FailureReason::SelectorError { .. })));
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::bail;
+use anyhow::Result;
+use serde_json::Map;
+use serde_json::Value;
+
+/// The style of the keys within an input or output JSON object.
+///
+/// The specification examples use the [`KeyStyle::Flattened`] style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum KeyStyle {
+    /// Keys are prefixed with the target name (e.g., `{"hello.matches": ...}`).
+    Flattened,
+    /// Keys are nested under the target name (e.g., `{"hello": {"matches": ...}}`).
+    Nested,
+    /// Keys are not prefixed with the target name (e.g., `{"matches": ...}`).
+    Unprefixed,
+}
+
+impl std::fmt::Display for KeyStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyStyle::Flattened => write!(f, "flattened"),
+            KeyStyle::Nested => write!(f, "nested"),
+            KeyStyle::Unprefixed => write!(f, "unprefixed"),
+        }
+    }
+}
+
+/// Converts a JSON object in the given style into the flattened style.
+pub fn flatten(value: &Value, style: KeyStyle, target: &str) -> Result<Value> {
+    let Some(obj) = value.as_object() else {
+        bail!("expected a JSON object to flatten, got `{value}`");
+    };
+
+    match style {
+        KeyStyle::Flattened => Ok(value.clone()),
+        KeyStyle::Nested => {
+            if obj.is_empty() {
+                return Ok(value.clone());
+            }
+
+            let inner = match obj.get(target) {
+                Some(Value::Object(inner)) if obj.len() == 1 => inner,
+                _ => bail!("expected a single object nested under the target `{target}`"),
+            };
+
+            Ok(Value::Object(prefix(inner, target)))
+        }
+        KeyStyle::Unprefixed => Ok(Value::Object(prefix(obj, target))),
+    }
+}
+
+/// Converts a JSON object in the flattened style into the given style.
+pub fn unflatten(value: &Value, style: KeyStyle, target: &str) -> Result<Value> {
+    let Some(obj) = value.as_object() else {
+        bail!("expected a JSON object to convert, got `{value}`");
+    };
+
+    if style == KeyStyle::Flattened {
+        return Ok(value.clone());
+    }
+
+    let mut unprefixed = Map::new();
+    let expected_prefix = format!("{target}.");
+
+    for (key, value) in obj {
+        let Some(name) = key.strip_prefix(&expected_prefix) else {
+            bail!("key `{key}` is not prefixed with the target `{target}`");
+        };
+
+        unprefixed.insert(name.to_string(), value.clone());
+    }
+
+    match style {
+        KeyStyle::Flattened => unreachable!(),
+        KeyStyle::Nested if unprefixed.is_empty() => Ok(Value::Object(unprefixed)),
+        KeyStyle::Nested => {
+            let mut nested = Map::new();
+            nested.insert(target.to_string(), Value::Object(unprefixed));
+            Ok(Value::Object(nested))
+        }
+        KeyStyle::Unprefixed => Ok(Value::Object(unprefixed)),
+    }
+}
+
+/// Prefixes every key in a JSON object with the target name.
+fn prefix(obj: &Map<String, Value>, target: &str) -> Map<String, Value> {
+    obj.iter()
+        .map(|(key, value)| (format!("{target}.{key}"), value.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flatten_flattened() {
+        let value = json!({"hello.matches": ["hello world"]});
+        let flattened = flatten(&value, KeyStyle::Flattened, "hello").unwrap();
+        assert_eq!(flattened, value);
+    }
+
+    #[test]
+    fn flatten_nested() {
+        let value = json!({"hello": {"matches": ["hello world"], "count": 1}});
+        let flattened = flatten(&value, KeyStyle::Nested, "hello").unwrap();
+        assert_eq!(flattened, json!({"hello.matches": ["hello world"], "hello.count": 1}));
+    }
+
+    #[test]
+    fn flatten_nested_wrong_target() {
+        let value = json!({"other": {"matches": ["hello world"]}});
+        let result = flatten(&value, KeyStyle::Nested, "hello");
+        assert!(result.unwrap_err().to_string().contains("`hello`"));
+    }
+
+    #[test]
+    fn flatten_unprefixed() {
+        let value = json!({"matches": ["hello world"]});
+        let flattened = flatten(&value, KeyStyle::Unprefixed, "hello").unwrap();
+        assert_eq!(flattened, json!({"hello.matches": ["hello world"]}));
+    }
+
+    #[test]
+    fn flatten_non_object() {
+        assert!(flatten(&json!([1, 2]), KeyStyle::Unprefixed, "hello").is_err());
+    }
+
+    #[test]
+    fn unflatten_nested() {
+        let value = json!({"hello.infile": "greetings.txt", "hello.pattern": "hello.*"});
+        let nested = unflatten(&value, KeyStyle::Nested, "hello").unwrap();
+        assert_eq!(nested, json!({"hello": {"infile": "greetings.txt", "pattern": "hello.*"}}));
+    }
+
+    #[test]
+    fn unflatten_unprefixed() {
+        let value = json!({"hello.infile": "greetings.txt"});
+        let unprefixed = unflatten(&value, KeyStyle::Unprefixed, "hello").unwrap();
+        assert_eq!(unprefixed, json!({"infile": "greetings.txt"}));
+    }
+
+    #[test]
+    fn unflatten_wrong_prefix() {
+        let value = json!({"other.infile": "greetings.txt"});
+        assert!(unflatten(&value, KeyStyle::Unprefixed, "hello").is_err());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let value = json!({"hello.infile": "greetings.txt", "hello.nested.value": 1});
+
+        for style in [KeyStyle::Flattened, KeyStyle::Nested, KeyStyle::Unprefixed] {
+            let converted = unflatten(&value, style, "hello").unwrap();
+            assert_eq!(flatten(&converted, style, "hello").unwrap(), value);
+        }
+    }
+}
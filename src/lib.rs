@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod command;
 pub mod conformance;
 pub mod repository;
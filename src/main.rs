@@ -2,12 +2,28 @@ use anyhow::Result;
 use clap::Parser;
 use clap::Subcommand;
 use clap_verbosity_flag::Verbosity;
+use spectool::command::cache::Args as CacheArgs;
+use spectool::command::diff_spec::Args as DiffSpecArgs;
+use spectool::command::matrix::Args as MatrixArgs;
+use spectool::command::probe::Args as ProbeArgs;
 use spectool::command::test::Args as TestArgs;
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Performs conformance tests on the WDL specification.
-    Test(TestArgs),
+    Test(Box<TestArgs>),
+
+    /// Detects the capabilities of an engine.
+    Probe(Box<ProbeArgs>),
+
+    /// Manages the specifications cached by `spectool test`.
+    Cache(CacheArgs),
+
+    /// Compares the conformance tests of two versions of the specification.
+    DiffSpec(Box<DiffSpecArgs>),
+
+    /// Compares the conformance of several engines.
+    Matrix(Box<MatrixArgs>),
 }
 
 /// A command-line tool for working with the WDL specification.
@@ -29,7 +45,11 @@ fn main() -> Result<()> {
         .init();
 
     match args.command {
-        Command::Test(args) => spectool::command::test::main(args)?,
+        Command::Test(args) => spectool::command::test::main(*args)?,
+        Command::Probe(args) => spectool::command::probe::main(*args)?,
+        Command::Cache(args) => spectool::command::cache::main(args)?,
+        Command::DiffSpec(args) => spectool::command::diff_spec::main(*args)?,
+        Command::Matrix(args) => spectool::command::matrix::main(*args)?,
     };
 
     Ok(())
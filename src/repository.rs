@@ -1,13 +1,30 @@
 use std::path::Path;
 use std::path::PathBuf;
 
+use anyhow::bail;
+use anyhow::Context;
 use anyhow::Result;
 use bon::Builder;
-use bon::builder;
+use git2::build::CheckoutBuilder;
+use git2::Direction;
+use git2::ErrorCode;
 use git2::FetchOptions;
+use git2::Oid;
+use tempfile::TempDir;
 use tracing::info;
+use tracing::warn;
 
-const REPOSITORY_URL: &str = "https://github.com/openwdl/wdl.git";
+/// The URL of the WDL specification repository.
+pub const REPOSITORY_URL: &str = "https://github.com/openwdl/wdl.git";
+
+/// The name of the remote within the repository.
+const REMOTE_NAME: &str = "origin";
+
+/// The prefix of branch references.
+const BRANCH_PREFIX: &str = "refs/heads/";
+
+/// The prefix of tag references.
+const TAG_PREFIX: &str = "refs/tags/";
 
 /// The WDL specification repository.
 #[derive(Builder)]
@@ -21,50 +38,409 @@ pub struct Repository {
     // want to create a new temporary directory with every test.
     local_dir: Option<PathBuf>,
 
-    /// The branch to check out.
+    /// The reference to check out.
+    ///
+    /// This may be a branch, a tag, a full or abbreviated commit SHA, or a
+    /// fully qualified reference such as `refs/pull/N/head`.
     #[builder(into)]
-    branch: String,
+    reference: String,
 
     /// The remote url.
-    #[builder(default = REPOSITORY_URL.to_owned())]
+    #[builder(into, default = REPOSITORY_URL.to_owned())]
     url: String,
+
+    /// Whether an existing checkout is fetched and fast-forwarded.
+    #[builder(default)]
+    update: bool,
+
+    /// Whether the network is not to be used.
+    ///
+    /// An offline checkout requires that the local directory already has
+    /// the reference checked out.
+    #[builder(default)]
+    offline: bool,
+}
+
+/// A checked out specification repository.
+pub struct Checkout {
+    /// The git repository.
+    repository: git2::Repository,
+
+    /// The path to the working tree.
+    path: PathBuf,
+
+    /// The commit that is checked out.
+    commit: Oid,
+
+    /// The temporary directory containing the working tree, if one was
+    /// created.
+    ///
+    /// The directory is removed when the checkout is dropped.
+    _tempdir: Option<TempDir>,
+}
+
+impl Checkout {
+    /// Gets the git repository.
+    pub fn repository(&self) -> &git2::Repository {
+        &self.repository
+    }
+
+    /// Gets the path to the working tree.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the commit that is checked out.
+    pub fn commit(&self) -> Oid {
+        self.commit
+    }
+}
+
+/// A reference advertised by the remote that a requested reference resolved
+/// to.
+struct RemoteRef {
+    /// The name of the reference on the remote.
+    name: String,
+}
+
+impl RemoteRef {
+    /// Gets the name of the local reference the remote reference is fetched
+    /// into.
+    fn local_name(&self) -> String {
+        match self.name.strip_prefix(BRANCH_PREFIX) {
+            Some(branch) => format!("refs/remotes/{REMOTE_NAME}/{branch}"),
+            None => self.name.clone(),
+        }
+    }
 }
 
 impl Repository {
-    /// Checks out the repository and returns a [`git2::Repository`].
-    pub fn checkout(self) -> Result<(git2::Repository, PathBuf)> {
-        let path = self.local_dir.unwrap_or_else(|| {
-            // SAFETY: on all the platforms we support, we expect a temporary
-            // directory to be able to be created.
-            let path = tempfile::tempdir()
-                .expect("temporary directory to create")
-                .into_path()
-                .join("wdl");
+    /// Checks out the repository.
+    ///
+    /// If the local directory already exists, it is assumed to be a checkout
+    /// from a previous run. A checkout of a different reference is switched
+    /// to the requested reference unless it has local modifications, and the
+    /// checkout is fast-forwarded if `update` is set. A checkout left
+    /// incomplete by an interrupted run is removed and checked out again.
+    pub fn checkout(self) -> Result<Checkout> {
+        let (path, tempdir) = match self.local_dir.clone() {
+            Some(path) => (path, None),
+            None => {
+                let tempdir = tempfile::tempdir().context("creating temporary directory for the repository")?;
+                let path = tempdir.path().join("wdl");
 
-            info!("created temporary directory for repository at `{}`", path.display());
+                info!("created temporary directory for repository at `{}`", path.display());
 
-            path
-        });
+                (path, Some(tempdir))
+            }
+        };
 
         if path.exists() {
-            // If the directory already exists, that directory is assumed to be
-            // the git repository checked out on a different run.
-            info!("using existing git repository");
-            return git2::Repository::open(&path)
-                .map(|repo| (repo, path))
-                .map_err(Into::into);
+            let repository = git2::Repository::open(&path)
+                .with_context(|| format!("opening git repository at `{}`", path.display()))?;
+
+            if self.is_incomplete(&repository) {
+                warn!("removing the incomplete git repository at `{}`", path.display());
+                drop(repository);
+                std::fs::remove_dir_all(&path)
+                    .with_context(|| format!("removing incomplete git repository at `{}`", path.display()))?;
+            } else {
+                info!("using existing git repository");
+                self.refresh(&repository, &path)?;
+                let commit = repository.head()?.peel_to_commit()?.id();
+
+                return Ok(Checkout {
+                    repository,
+                    path,
+                    commit,
+                    _tempdir: tempdir,
+                });
+            }
+        }
+
+        if self.offline {
+            bail!(
+                "`{}` has not been checked out to `{}`, which is required when offline",
+                self.reference,
+                path.display()
+            );
+        }
+
+        info!("creating new git repository with reference `{}`", self.reference);
+
+        let repository = git2::Repository::init(&path)
+            .with_context(|| format!("creating git repository at `{}`", path.display()))?;
+        let commit = {
+            let mut remote = repository.remote(REMOTE_NAME, &self.url)?;
+            self.fetch(&repository, &mut remote, true)
+                .and_then(|(commit, branch)| {
+                    self.switch(&repository, &commit, branch.as_deref())?;
+                    Ok(commit.id())
+                })
+                .inspect_err(|_| {
+                    // NOTE: an incomplete checkout would otherwise be mistaken
+                    // for a checkout from a previous run.
+                    let _ = std::fs::remove_dir_all(&path);
+                })?
+        };
+
+        Ok(Checkout {
+            repository,
+            path,
+            commit,
+            _tempdir: tempdir,
+        })
+    }
+
+    /// Returns whether a repository is a checkout that was interrupted
+    /// before anything was checked out.
+    ///
+    /// Only repositories with the remote of a new checkout are considered,
+    /// so that a repository created by other means is never removed.
+    fn is_incomplete(&self, repository: &git2::Repository) -> bool {
+        let unborn = matches!(repository.head(), Err(e) if e.code() == ErrorCode::UnbornBranch);
+
+        unborn
+            && repository
+                .find_remote(REMOTE_NAME)
+                .is_ok_and(|remote| remote.url() == Some(&self.url))
+    }
+
+    /// Brings an existing checkout to the requested reference.
+    fn refresh(&self, repository: &git2::Repository, path: &Path) -> Result<()> {
+        let modified = has_local_modifications(repository)?;
+        if modified {
+            warn!("the specification repository at `{}` has local modifications", path.display());
+        }
+
+        if !self.is_checked_out(repository)? {
+            let current = describe_head(repository)?;
+
+            if self.offline {
+                bail!(
+                    "the specification repository at `{}` has `{}` checked out rather than `{}`, and it cannot be \
+                     switched when offline",
+                    path.display(),
+                    current,
+                    self.reference
+                );
+            }
+
+            if modified {
+                bail!(
+                    "the specification repository at `{}` has `{}` checked out rather than `{}`, and it cannot be \
+                     switched because it has local modifications",
+                    path.display(),
+                    current,
+                    self.reference
+                );
+            }
+
+            warn!(
+                "switching the specification repository at `{}` from `{}` to `{}`",
+                path.display(),
+                current,
+                self.reference
+            );
+
+            let mut remote = repository.remote_anonymous(&self.url)?;
+            let (commit, branch) = self.fetch(repository, &mut remote, false)?;
+            return self.switch(repository, &commit, branch.as_deref());
+        }
+
+        if self.update && self.offline {
+            warn!("not updating the specification repository because the network is not to be used");
+        } else if self.update {
+            let mut remote = repository.remote_anonymous(&self.url)?;
+            let (fetched, _) = self.fetch(repository, &mut remote, false)?;
+            let head = repository.head()?.peel_to_commit()?;
+
+            if fetched.id() == head.id() {
+                info!("the specification repository is up to date");
+            } else if modified {
+                bail!(
+                    "the specification repository at `{}` cannot be updated because it has local modifications",
+                    path.display()
+                );
+            } else if repository.graph_descendant_of(fetched.id(), head.id())? {
+                info!("fast-forwarding `{}` to commit `{}`", self.reference, fetched.id());
+                repository.checkout_tree(fetched.as_object(), None)?;
+
+                let mut head_ref = repository.head()?;
+                if head_ref.is_branch() {
+                    head_ref.set_target(fetched.id(), "spectool: fast-forward")?;
+                } else {
+                    repository.set_head_detached(fetched.id())?;
+                }
+            } else {
+                bail!(
+                    "the specification repository at `{}` cannot be fast-forwarded to the latest `{}`",
+                    path.display(),
+                    self.reference
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the requested reference is checked out.
+    fn is_checked_out(&self, repository: &git2::Repository) -> Result<bool> {
+        let head = repository.head()?;
+
+        if head.is_branch() && (head.shorthand() == Some(&self.reference) || head.name() == Some(&self.reference)) {
+            return Ok(true);
+        }
+
+        let commit = head.peel_to_commit()?.id();
+        Ok(repository
+            .revparse_single(&self.reference)
+            .and_then(|object| object.peel_to_commit())
+            .is_ok_and(|requested| requested.id() == commit))
+    }
+
+    /// Fetches the reference from a remote.
+    ///
+    /// Returns the fetched commit and, if the reference is a branch, the name
+    /// of the branch.
+    fn fetch<'a>(
+        &self,
+        repository: &'a git2::Repository,
+        remote: &mut git2::Remote<'_>,
+        shallow: bool,
+    ) -> Result<(git2::Commit<'a>, Option<String>)> {
+        remote
+            .connect(Direction::Fetch)
+            .with_context(|| format!("connecting to `{}`", self.url))?;
+        let advertised = remote
+            .list()?
+            .iter()
+            .map(|head| (head.name().to_string(), head.oid()))
+            .collect::<Vec<_>>();
+        remote.disconnect()?;
+
+        match resolve(&self.reference, &advertised) {
+            Some(remote_ref) => {
+                let local_name = remote_ref.local_name();
+                let refspec = format!("+{}:{}", remote_ref.name, local_name);
+
+                remote
+                    .fetch(&[&refspec], Some(&mut self.fetch_options(shallow)), None)
+                    .with_context(|| format!("fetching `{}` from `{}`", remote_ref.name, self.url))?;
+
+                let commit = repository.find_reference(&local_name)?.peel_to_commit()?;
+                let branch = remote_ref.name.strip_prefix(BRANCH_PREFIX).map(ToOwned::to_owned);
+                Ok((commit, branch))
+            }
+            None if is_commit_sha(&self.reference) => Ok((self.fetch_commit(repository, remote, shallow)?, None)),
+            None => bail!(
+                "`{}` is not a branch, tag, commit, or reference within `{}`",
+                self.reference,
+                self.url
+            ),
+        }
+    }
+
+    /// Checks out a commit.
+    ///
+    /// Branches are checked out as a local branch, which must not have
+    /// diverged from the commit if it already exists. Any other reference is
+    /// checked out as a detached `HEAD`.
+    fn switch(&self, repository: &git2::Repository, commit: &git2::Commit<'_>, branch: Option<&str>) -> Result<()> {
+        // NOTE: a diverged local branch is rejected before the working tree is
+        // touched, so a failed switch leaves the checkout as it was.
+        let local = match branch {
+            Some(branch) => match repository.find_branch(branch, git2::BranchType::Local) {
+                Ok(local) => {
+                    let local = local.into_reference();
+                    let target = local.peel_to_commit()?.id();
+
+                    if target != commit.id() && !repository.graph_descendant_of(commit.id(), target)? {
+                        bail!("the local branch `{branch}` has diverged from `{}`", self.url);
+                    }
+
+                    Some(local)
+                }
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        repository.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))?;
+
+        match branch {
+            Some(branch) => {
+                match local {
+                    Some(mut local) => _ = local.set_target(commit.id(), "spectool: fast-forward")?,
+                    None => {
+                        let mut local = repository.branch(branch, commit, false)?;
+
+                        let has_origin = repository
+                            .find_remote(REMOTE_NAME)
+                            .is_ok_and(|remote| remote.url() == Some(&self.url));
+                        if has_origin {
+                            local.set_upstream(Some(&format!("{REMOTE_NAME}/{branch}")))?;
+                        }
+                    }
+                }
+
+                repository.set_head(&format!("{BRANCH_PREFIX}{branch}"))?;
+            }
+            None => repository.set_head_detached(commit.id())?,
         }
 
-        info!("creating new git repository with branch `{branch}`", branch = self.branch);
+        info!("checked out `{}` at commit `{}`", self.reference, commit.id());
+        Ok(())
+    }
+
+    /// Fetches a commit by its full or abbreviated SHA.
+    ///
+    /// A full SHA is fetched directly if the remote allows it. Otherwise, the
+    /// full history of every branch is fetched to find the commit.
+    fn fetch_commit<'a>(
+        &self,
+        repository: &'a git2::Repository,
+        remote: &mut git2::Remote<'_>,
+        shallow: bool,
+    ) -> Result<git2::Commit<'a>> {
+        if self.reference.len() == 40 {
+            let oid = Oid::from_str(&self.reference)?;
+
+            if remote
+                .fetch(&[&self.reference], Some(&mut self.fetch_options(shallow)), None)
+                .is_ok()
+            {
+                if let Ok(commit) = repository.find_commit(oid) {
+                    return Ok(commit);
+                }
+            }
+        }
+
+        info!("fetching all branches to find commit `{}`", self.reference);
+        let refspec = format!("+{BRANCH_PREFIX}*:refs/remotes/{REMOTE_NAME}/*");
+        remote
+            .fetch(&[&refspec], None, None)
+            .with_context(|| format!("fetching branches from `{}`", self.url))?;
+
+        repository
+            .find_commit_by_prefix(&self.reference)
+            .with_context(|| format!("finding commit `{}` within `{}`", self.reference, self.url))
+    }
+
+    /// Gets the options for fetching, optionally only fetching the requested
+    /// commit.
+    ///
+    /// Shallow fetches are not supported by the local transport, so the
+    /// full history is fetched from local mirrors.
+    fn fetch_options(&self, shallow: bool) -> FetchOptions<'static> {
         let mut fetch_options = FetchOptions::new();
-        fetch_options.depth(1);
 
-        git2::build::RepoBuilder::new()
-            .branch(&self.branch)
-            .fetch_options(fetch_options)
-            .clone(&self.url, &path)
-            .map(|repo| (repo, path))
-            .map_err(Into::into)
+        let is_local = self.url.starts_with("file://") || Path::new(&self.url).exists();
+        if shallow && !is_local {
+            fetch_options.depth(1);
+        }
+
+        fetch_options
     }
 
     /// Gets a reference to the local directory.
@@ -72,21 +448,235 @@ impl Repository {
         self.local_dir.as_deref()
     }
 
+    /// Gets the reference to check out.
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
     /// Gets a reference to the URL.
     pub fn url(&self) -> &str {
         &self.url
     }
 }
 
+/// Returns whether the working tree of a repository has modifications to
+/// tracked files.
+fn has_local_modifications(repository: &git2::Repository) -> Result<bool> {
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+
+    Ok(!repository.statuses(Some(&mut options))?.is_empty())
+}
+
+/// Describes what is checked out within a repository.
+///
+/// This is the name of the branch or, for a detached `HEAD`, the commit.
+fn describe_head(repository: &git2::Repository) -> Result<String> {
+    let head = repository.head()?;
+
+    match head.shorthand() {
+        Some(branch) if head.is_branch() => Ok(branch.to_string()),
+        _ => Ok(head.peel_to_commit()?.id().to_string()),
+    }
+}
+
+/// Resolves a requested reference against the references advertised by the
+/// remote.
+///
+/// Fully qualified references are matched exactly, then branches are
+/// preferred over tags. Finally, a commit SHA that a reference points to is
+/// resolved to that reference.
+fn resolve(reference: &str, advertised: &[(String, Oid)]) -> Option<RemoteRef> {
+    let find = |name: &str| {
+        advertised
+            .iter()
+            .find(|(advertised, _)| advertised == name)
+            .map(|(name, _)| RemoteRef { name: name.clone() })
+    };
+
+    if reference.starts_with("refs/") {
+        return find(reference);
+    }
+
+    find(&format!("{BRANCH_PREFIX}{reference}"))
+        .or_else(|| find(&format!("{TAG_PREFIX}{reference}")))
+        .or_else(|| {
+            if !is_commit_sha(reference) {
+                return None;
+            }
+
+            let reference = reference.to_ascii_lowercase();
+            advertised
+                .iter()
+                .filter(|(name, _)| name.starts_with(BRANCH_PREFIX) || name.starts_with(TAG_PREFIX))
+                .find(|(name, oid)| !name.ends_with("^{}") && oid.to_string().starts_with(&reference))
+                .map(|(name, _)| RemoteRef { name: name.clone() })
+        })
+}
+
+/// Returns whether a reference looks like a full or abbreviated commit SHA.
+fn is_commit_sha(reference: &str) -> bool {
+    (7..=40).contains(&reference.len()) && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn default_url() {
-        let repo = Repository::builder().branch("main").build();
+        let repo = Repository::builder().reference("main").build();
 
         assert!(repo.local_dir.is_none());
         assert_eq!(repo.url(), REPOSITORY_URL);
     }
+
+    #[test]
+    fn resolves_references() {
+        let oid = |s: &str| Oid::from_str(s).unwrap();
+        let advertised = vec![
+            ("refs/heads/wdl-1.2".to_string(), oid("4220a3e1ad8c6287cd9857674ddd8b5459bdac58")),
+            ("refs/tags/wdl-1.2".to_string(), oid("507cade27106e4935c245ea6f03656a66d0124e2")),
+            ("refs/tags/v1.2.0".to_string(), oid("7e34e01f808a2cabadad50e035d5b3e9c3f720ed")),
+            ("refs/pull/7/head".to_string(), oid("dc52b219e770c1ced8eed92c8a145f22fe326d87")),
+        ];
+
+        let resolved = resolve("wdl-1.2", &advertised).unwrap();
+        assert_eq!(resolved.name, "refs/heads/wdl-1.2");
+        assert_eq!(resolved.local_name(), "refs/remotes/origin/wdl-1.2");
+
+        let resolved = resolve("v1.2.0", &advertised).unwrap();
+        assert_eq!(resolved.local_name(), "refs/tags/v1.2.0");
+
+        let resolved = resolve("refs/pull/7/head", &advertised).unwrap();
+        assert_eq!(resolved.local_name(), "refs/pull/7/head");
+
+        let resolved = resolve("4220a3e", &advertised).unwrap();
+        assert_eq!(resolved.name, "refs/heads/wdl-1.2");
+
+        assert!(resolve("refs/pull/8/head", &advertised).is_none());
+        assert!(resolve("deadbeef", &advertised).is_none());
+        assert!(resolve("wdl-1.3", &advertised).is_none());
+    }
+
+    #[test]
+    fn detects_commit_shas() {
+        assert!(is_commit_sha("4220a3e"));
+        assert!(is_commit_sha("4220a3e1ad8c6287cd9857674ddd8b5459bdac58"));
+        assert!(!is_commit_sha("wdl-1.2"));
+        assert!(!is_commit_sha("abc"));
+    }
+
+    /// Commits a file to a branch of a repository.
+    fn commit(repository: &git2::Repository, branch: &str, contents: &str) -> Oid {
+        let path = repository.workdir().unwrap().join("SPEC.md");
+        std::fs::write(&path, contents).unwrap();
+
+        let mut index = repository.index().unwrap();
+        index.add_path(Path::new("SPEC.md")).unwrap();
+        index.write().unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let signature = git2::Signature::now("spectool", "spectool@example.com").unwrap();
+        let reference = format!("{BRANCH_PREFIX}{branch}");
+        let parent = repository
+            .find_reference(&reference)
+            .and_then(|reference| reference.peel_to_commit())
+            .ok();
+
+        repository
+            .commit(
+                Some(&reference),
+                &signature,
+                &signature,
+                contents,
+                &tree,
+                parent.as_ref().into_iter().collect::<Vec<_>>().as_slice(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn existing_checkouts() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let remote = git2::Repository::init(tempdir.path().join("remote")).unwrap();
+        let url = format!("file://{}", tempdir.path().join("remote").display());
+        let local_dir = tempdir.path().join("local");
+
+        let first = commit(&remote, "wdl-1.1", "first");
+        let other = commit(&remote, "wdl-1.2", "other");
+
+        let checkout = |reference: &str, update: bool| {
+            Repository::builder()
+                .reference(reference)
+                .url(&url)
+                .local_dir(local_dir.clone())
+                .update(update)
+                .build()
+                .checkout()
+                .map(|checkout| checkout.commit())
+        };
+
+        assert_eq!(checkout("wdl-1.1", false).unwrap(), first);
+
+        // A different reference is switched to.
+        assert_eq!(checkout("wdl-1.2", false).unwrap(), other);
+        assert_eq!(checkout("wdl-1.1", false).unwrap(), first);
+
+        // New commits are only fetched when updating.
+        let second = commit(&remote, "wdl-1.1", "second");
+        assert_eq!(checkout("wdl-1.1", false).unwrap(), first);
+        assert_eq!(checkout("wdl-1.1", true).unwrap(), second);
+        assert_eq!(std::fs::read_to_string(local_dir.join("SPEC.md")).unwrap(), "second");
+
+        // Local modifications prevent switching.
+        std::fs::write(local_dir.join("SPEC.md"), "modified").unwrap();
+        assert!(checkout("wdl-1.2", false).is_err());
+        assert_eq!(checkout("wdl-1.1", false).unwrap(), second);
+
+        // A diverged local branch prevents switching without touching the
+        // working tree.
+        let local = git2::Repository::open(&local_dir).unwrap();
+        commit(&local, "wdl-1.1", "local");
+        assert_eq!(checkout("wdl-1.2", false).unwrap(), other);
+        commit(&remote, "wdl-1.1", "third");
+        assert!(checkout("wdl-1.1", true).is_err());
+        assert_eq!(std::fs::read_to_string(local_dir.join("SPEC.md")).unwrap(), "other");
+        assert_eq!(local.head().unwrap().shorthand(), Some("wdl-1.2"));
+    }
+
+    #[test]
+    fn incomplete_checkouts() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let remote = git2::Repository::init(tempdir.path().join("remote")).unwrap();
+        let url = format!("file://{}", tempdir.path().join("remote").display());
+        let first = commit(&remote, "wdl-1.1", "first");
+
+        let checkout = |local_dir: Option<PathBuf>| {
+            Repository::builder()
+                .reference("wdl-1.1")
+                .url(&url)
+                .maybe_local_dir(local_dir)
+                .build()
+                .checkout()
+        };
+
+        // An interrupted checkout is checked out again.
+        let local_dir = tempdir.path().join("local");
+        git2::Repository::init(&local_dir).unwrap().remote(REMOTE_NAME, &url).unwrap();
+        assert_eq!(checkout(Some(local_dir.clone())).unwrap().commit(), first);
+
+        // Any other repository without commits is left as is.
+        let other_dir = tempdir.path().join("other");
+        git2::Repository::init(&other_dir).unwrap();
+        assert!(checkout(Some(other_dir.clone())).is_err());
+        assert!(other_dir.join(".git").exists());
+
+        // A temporary checkout is removed when dropped.
+        let temporary = checkout(None).unwrap();
+        let path = temporary.path().to_path_buf();
+        assert_eq!(std::fs::read_to_string(path.join("SPEC.md")).unwrap(), "first");
+        drop(temporary);
+        assert!(!path.exists());
+    }
 }
@@ -1,19 +1,135 @@
 use std::path::PathBuf;
+use std::sync::LazyLock;
 
+use anyhow::bail;
+use anyhow::Result;
 use bon::builder;
+use regex::Captures;
+use regex::Regex;
 
 use crate::conformance::Target;
 
+/// The regex for a placeholder within a command template.
+static PLACEHOLDER_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"~\{([^}]*)\}").unwrap());
+
+/// The prefix of a placeholder that refers to an environment variable.
+const ENV_PREFIX: &str = "env:";
+
+/// The names of the placeholders supported within a command template.
+const PLACEHOLDERS: &[&str] = &[
+    "path",
+    "input",
+    "output",
+    "target",
+    "workdir",
+    "data_dir",
+    "test_name",
+    "target_kind",
+    "root_dir",
+    "wdl_version",
+];
+
+/// A lookup of the value of an environment variable.
+pub type Env = fn(&str) -> Option<String>;
+
+/// Looks up an environment variable of the current process.
+///
+/// Values that are not valid UTF-8 are converted lossily.
+pub fn process_env(name: &str) -> Option<String> {
+    std::env::var_os(name).map(|value| value.to_string_lossy().into_owned())
+}
+
+/// Validates that every placeholder within a command template is supported.
+///
+/// Environment variable placeholders (`~{env:NAME}`) must refer to a variable
+/// that is set within `env`.
+pub fn validate(template: &str, env: Env) -> Result<()> {
+    for captures in PLACEHOLDER_REGEX.captures_iter(template) {
+        let name = &captures[1];
+
+        if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+            if var.is_empty() {
+                bail!("placeholder `~{{{name}}}` is missing an environment variable name");
+            }
+
+            if env(var).is_none() {
+                bail!("environment variable `{var}` referenced by `~{{{name}}}` is not set");
+            }
+        } else if !PLACEHOLDERS.contains(&name) {
+            bail!(
+                "unknown placeholder `~{{{name}}}` (supported placeholders are {}, and `~{{env:NAME}}`)",
+                PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("`~{{{p}}}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a command into its arguments using shell quoting rules.
+///
+/// No other shell features (e.g., variable expansion, pipes, or
+/// redirections) are interpreted.
+pub fn split(command: &str) -> Result<Vec<String>> {
+    match shlex::split(command) {
+        Some(args) if !args.is_empty() => Ok(args),
+        Some(_) => bail!("command is empty"),
+        None => bail!("command has unbalanced quotes or a trailing escape: `{command}`"),
+    }
+}
+
+/// A command with its placeholders substituted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// A command line to be run through a shell.
+    Shell(String),
+
+    /// The arguments of a program to be spawned directly.
+    Exec(Vec<String>),
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Shell(command) => write!(f, "{command}"),
+            Command::Exec(args) => {
+                let joined = shlex::try_join(args.iter().map(String::as_str)).unwrap_or_else(|_| args.join(" "));
+                write!(f, "{joined}")
+            }
+        }
+    }
+}
+
 /// Builds the command with substitutions and target-specific arguments.
 ///
 /// Substitutions:
 /// - `~{path}` → path to the WDL file
 /// - `~{input}` → path to the inputs.json file
 /// - `~{output}` → path to the outputs.json file
-/// - `~{target}` in target args → workflow or task name
+/// - `~{target}` → workflow or task name
+/// - `~{workdir}` → path to the working directory of the test
+/// - `~{data_dir}` → path to the data directory within the working directory
+/// - `~{test_name}` → name of the test (the file name without `.wdl`)
+/// - `~{target_kind}` → `workflow` or `task`
+/// - `~{root_dir}` → path to the directory containing the WDL files
+/// - `~{wdl_version}` → version of the WDL file
+/// - `~{env:NAME}` → value of the `NAME` environment variable within `env`
+///   (the environment of the current process by default), verbatim
+///
+/// For a shell command, all substituted values other than environment
+/// variables are shell-quoted when necessary. If `exec` is set, the template
+/// is first split into arguments using shell quoting rules and the
+/// placeholders are then substituted within each argument without any
+/// quoting, so a substituted value is never split into several arguments.
+/// Templates are expected to have been checked with [`validate()`]; unknown
+/// placeholders are left as is.
 ///
 /// The appropriate target args template is selected based on the target type
-/// and appended to the command after substitutions.
+/// and appended to the command before substitutions.
 #[builder]
 pub fn substitute(
     mut command: String,
@@ -23,21 +139,178 @@ pub fn substitute(
     target: Target,
     workflow_target_args: String,
     task_target_args: String,
-) -> String {
+    workdir: PathBuf,
+    root_dir: PathBuf,
+    test_name: String,
+    wdl_version: Option<String>,
+    #[builder(default)] exec: bool,
+    #[builder(default = process_env)] env: Env,
+) -> Result<Command> {
     // Select the appropriate target args template and substitute target name
-    let target_args = match &target {
-        Target::Workflow(_) => workflow_target_args,
-        Target::Task(_) => task_target_args,
+    let (target_args, target_kind) = match &target {
+        Target::Workflow(_) => (workflow_target_args, "workflow"),
+        Target::Task(_) => (task_target_args, "task"),
     };
 
     // Append target args to command
     command.push(' ');
     command.push_str(&target_args);
 
-    command = command.replace("~{path}", &path.display().to_string());
-    command = command.replace("~{input}", &input.display().to_string());
-    command = command.replace("~{output}", &output.display().to_string());
-    command = command.replace("~{target}", target.name());
+    let data_dir = workdir.join("data");
+
+    let value = |name: &str| -> Option<String> {
+        if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+            return Some(env(var).unwrap_or_default());
+        }
+
+        let value = match name {
+            "path" => path.display().to_string(),
+            "input" => input.display().to_string(),
+            "output" => output.display().to_string(),
+            "target" => target.name().to_string(),
+            "workdir" => workdir.display().to_string(),
+            "data_dir" => data_dir.display().to_string(),
+            "test_name" => test_name.clone(),
+            "target_kind" => target_kind.to_string(),
+            "root_dir" => root_dir.display().to_string(),
+            "wdl_version" => wdl_version.clone().unwrap_or_default(),
+            _ => return None,
+        };
+
+        Some(value)
+    };
+
+    let replace = |template: &str, quoted: bool| {
+        PLACEHOLDER_REGEX
+            .replace_all(template, |captures: &Captures<'_>| {
+                let name = &captures[1];
+
+                match value(name) {
+                    Some(value) if quoted && !name.starts_with(ENV_PREFIX) => quote(&value),
+                    Some(value) => value,
+                    None => captures[0].to_string(),
+                }
+            })
+            .into_owned()
+    };
+
+    if exec {
+        let args = split(&command)?;
+        Ok(Command::Exec(args.iter().map(|arg| replace(arg, false)).collect()))
+    } else {
+        Ok(Command::Shell(replace(&command, true)))
+    }
+}
+
+/// Quotes a value for use within a shell command.
+///
+/// Values made up entirely of characters that are not special to the shell
+/// are returned as is.
+fn quote(value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c);
+
+    if !value.is_empty() && value.chars().all(is_safe) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An environment in which only `SPECTOOL_VALUE` is set.
+    fn env(name: &str) -> Option<String> {
+        (name == "SPECTOOL_VALUE").then(|| String::from(r#"a "b" c'd"#))
+    }
+
+    fn call(command: &str, workdir: &str) -> String {
+        build(command, workdir, false).unwrap().to_string()
+    }
+
+    fn build(command: &str, workdir: &str, exec: bool) -> Result<Command> {
+        substitute()
+            .command(command.to_string())
+            .path(PathBuf::from("/tests/hello.wdl"))
+            .input(PathBuf::from(workdir).join("inputs.json"))
+            .output(PathBuf::from(workdir).join("outputs.json"))
+            .target(Target::Workflow("hello".to_string()))
+            .workflow_target_args("-e ~{target}".to_string())
+            .task_target_args("-t ~{target}".to_string())
+            .workdir(PathBuf::from(workdir))
+            .root_dir(PathBuf::from("/tests"))
+            .test_name("hello".to_string())
+            .wdl_version("1.2".to_string())
+            .exec(exec)
+            .env(env)
+            .call()
+    }
+
+    #[test]
+    fn substitutes_placeholders() {
+        let command = call(
+            "run ~{path} ~{input} ~{output} ~{workdir} ~{data_dir} ~{test_name} ~{target_kind} ~{root_dir} ~{wdl_version}",
+            "/work/hello",
+        );
+
+        assert_eq!(
+            command,
+            "run /tests/hello.wdl /work/hello/inputs.json /work/hello/outputs.json /work/hello \
+             /work/hello/data hello workflow /tests 1.2 -e hello"
+        );
+    }
+
+    #[test]
+    fn quotes_paths() {
+        let command = call("run ~{input}", "/my work/it's");
+        assert_eq!(command, r"run '/my work/it'\''s/inputs.json' -e hello");
+    }
+
+    #[test]
+    fn substitutes_environment_variables() {
+        let command = call("run ~{env:SPECTOOL_VALUE} ~{env:UNSET}", "/work");
+        assert_eq!(command, r#"run a "b" c'd  -e hello"#);
+    }
+
+    #[test]
+    fn splits_before_substituting() {
+        let command = build(
+            "run ~{input} --name 'my test' --value ~{env:SPECTOOL_VALUE} --dir=~{workdir}",
+            r#"/my work/it's "here""#,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            command,
+            Command::Exec(
+                [
+                    "run",
+                    r#"/my work/it's "here"/inputs.json"#,
+                    "--name",
+                    "my test",
+                    "--value",
+                    r#"a "b" c'd"#,
+                    r#"--dir=/my work/it's "here""#,
+                    "-e",
+                    "hello",
+                ]
+                .map(String::from)
+                .to_vec()
+            )
+        );
+
+        assert!(build("run 'unbalanced", "/work", true).is_err());
+        assert!(split("run 'unbalanced").is_err());
+        assert!(split("  ").is_err());
+    }
 
-    command
+    #[test]
+    fn validates_placeholders() {
+        assert!(validate("run ~{path} ~{input} ~{env:SPECTOOL_VALUE}", env).is_ok());
+        assert!(validate("run ~{pth}", env).unwrap_err().to_string().contains("`~{pth}`"));
+        assert!(validate("run ~{env:}", env).is_err());
+        assert!(validate("run ~{env:PATH}", env).is_err());
+    }
 }
@@ -32,6 +32,52 @@ static TASK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?m)^\s*task\s+(\w+)\s*\{").unwrap()
 });
 
+/// Regex to match the version statement in WDL.
+static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*version\s+(\S+)").unwrap()
+});
+
+/// Regex to match the start of any top-level document item in WDL.
+static ITEM_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:workflow|task|struct)\s+\w+\s*\{").unwrap()
+});
+
+/// Regex to match the start of an input section in WDL.
+static INPUT_SECTION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*input\s*\{").unwrap()
+});
+
+/// Regex to match a single declaration within an input section in WDL.
+static DECLARATION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\S.*?)\s+(\w+)\s*(?:=.*)?$").unwrap()
+});
+
+/// Regex to match the WDL types that refer to paths.
+static PATH_TYPE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:File|Directory)\b").unwrap()
+});
+
+/// An input declaration found in a WDL file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputDeclaration {
+    /// The declared type.
+    ty: String,
+    /// The name of the input.
+    name: String,
+}
+
+impl InputDeclaration {
+    /// Gets the name of the input.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns whether the declared type contains a `File` or `Directory`.
+    pub fn is_path(&self) -> bool {
+        PATH_TYPE_REGEX.is_match(&self.ty)
+    }
+}
+
 /// The declarations found in a WDL file.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WdlDeclarations {
@@ -88,6 +134,72 @@ pub fn parse_wdl_declarations(source: &str) -> Result<WdlDeclarations> {
     Ok(WdlDeclarations { workflow, tasks })
 }
 
+/// Parses WDL source code to extract the version from the version statement.
+pub fn parse_version(source: &str) -> Option<&str> {
+    VERSION_REGEX
+        .captures(source)
+        .and_then(|cap| cap.get(1))
+        .map(|m| m.as_str())
+}
+
+/// Parses WDL source code to extract the input declarations of a target.
+///
+/// Like [`parse_wdl_declarations`], this is a minimal regex-based parser. It
+/// returns an empty list if the target or its input section cannot be found.
+pub fn parse_input_declarations(source: &str, target: &Target) -> Vec<InputDeclaration> {
+    let keyword = match target {
+        Target::Task(_) => "task",
+        Target::Workflow(_) => "workflow",
+    };
+
+    // SAFETY: the target name is escaped, so the pattern is always valid.
+    let target_regex = Regex::new(&format!(
+        r"(?m)^\s*{keyword}\s+{}\s*\{{",
+        regex::escape(target.name())
+    ))
+    .unwrap();
+
+    let Some(start) = target_regex.find(source) else {
+        return Vec::new();
+    };
+
+    // Only search until the next document item
+    let body = &source[start.end()..];
+    let body = match ITEM_REGEX.find(body) {
+        Some(next) => &body[..next.start()],
+        None => body,
+    };
+
+    let Some(section) = INPUT_SECTION_REGEX.find(body) else {
+        return Vec::new();
+    };
+
+    let mut depth = 1;
+    let mut end = body.len();
+    for (i, c) in body[section.end()..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+
+        if depth == 0 {
+            end = section.end() + i;
+            break;
+        }
+    }
+
+    body[section.end()..end]
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| DECLARATION_REGEX.captures(line))
+        .map(|cap| InputDeclaration {
+            ty: cap[1].to_string(),
+            name: cap[2].to_string(),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,4 +293,69 @@ mod tests {
         assert_eq!(decls.tasks(), &[] as &[String]);
         assert_eq!(decls.single_target(), None);
     }
+
+    #[test]
+    fn version() {
+        assert_eq!(parse_version("# comment\nversion 1.2\n\nworkflow w {}"), Some("1.2"));
+        assert_eq!(parse_version("  version development\n"), Some("development"));
+        assert_eq!(parse_version("workflow w {}"), None);
+    }
+
+    #[test]
+    fn input_declarations() {
+        let wdl = r#"
+            version 1.2
+
+            task hello_task {
+                input {
+                    File infile
+                    String pattern
+                }
+
+                command <<<
+                    grep -E '~{pattern}' '~{infile}'
+                >>>
+            }
+
+            workflow hello {
+                input {
+                    File infile  # the file to search
+                    Array[File]+ others = []
+                    Map[String, Directory] dirs
+                    String? pattern = "hello"
+                    Int count
+                }
+            }
+        "#;
+
+        let target = Target::Workflow("hello".to_string());
+        let decls = parse_input_declarations(wdl, &target);
+        let names = decls.iter().map(|d| d.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["infile", "others", "dirs", "pattern", "count"]);
+
+        let paths = decls.iter().filter(|d| d.is_path()).map(|d| d.name()).collect::<Vec<_>>();
+        assert_eq!(paths, ["infile", "others", "dirs"]);
+        assert_eq!(decls[2].ty, "Map[String, Directory]");
+    }
+
+    #[test]
+    fn input_declarations_missing_section() {
+        let wdl = r#"
+            version 1.2
+            task no_inputs {
+                command { echo "1" }
+            }
+            task with_inputs {
+                input {
+                    File infile
+                }
+            }
+        "#;
+
+        let target = Target::Task("no_inputs".to_string());
+        assert!(parse_input_declarations(wdl, &target).is_empty());
+
+        let target = Target::Task("missing".to_string());
+        assert!(parse_input_declarations(wdl, &target).is_empty());
+    }
 }